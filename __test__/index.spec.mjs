@@ -1,6 +1,6 @@
 import test from 'ava'
 
-import { ByteBuf } from '../index.js'
+import { ByteBuf, ByteBufCursor, ByteOrder, StringEncoding } from '../index.js'
 
 test('test byte buffer creation', (t) => {
   const buf = new ByteBuf();
@@ -82,8 +82,1987 @@ test('test read short', (t) => {
 test('test read with no bytes left', (t) => {
   const buf = new ByteBuf(Buffer.from([0x7f]));
   t.is(buf.readByte(), 0x7f);
-  t.throws(() => buf.readByte(), { message: 'cannot readByte, readableBytes is less than 1' });
-  t.throws(() => buf.readBoolean(), { message: 'cannot readBoolean, readableBytes is less than 1' });
+  t.throws(() => buf.readByte(), { message: 'cannot readByte, readableBytes 0 is less than 1 at readerIndex 1' });
+  t.throws(() => buf.readBoolean(), { message: 'cannot readBoolean, readableBytes 0 is less than 1 at readerIndex 1' });
 })
 
 
+
+test('test inspect', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0xab, 0xff]));
+  buf.skipBytes(1);
+  t.is(buf.inspect(), 'ByteBuf(readerIndex=1, writerIndex=4, capacity=4, readable=3) [02 ab ff]');
+
+  const buf2 = new ByteBuf(Buffer.alloc(20));
+  t.is(
+    buf2.inspect(),
+    'ByteBuf(readerIndex=0, writerIndex=20, capacity=20, readable=20) [' + Array(16).fill('00').join(' ') + ' ...]'
+  );
+})
+
+test('test sized byte array', (t) => {
+  for (const width of [1, 2, 4]) {
+    const buf = new ByteBuf();
+    buf.writeSizedByteArray(width, Buffer.from([1, 2, 3]));
+    t.is(buf.getReadableBytes(), width + 3);
+    t.deepEqual(buf.readSizedByteArray(width), Buffer.from([1, 2, 3]));
+    t.is(buf.getReadableBytes(), 0);
+  }
+
+  const buf = new ByteBuf();
+  t.throws(() => buf.writeSizedByteArray(3, Buffer.from([1])), { code: 'InvalidArg' });
+  t.throws(() => buf.writeSizedByteArray(1, Buffer.alloc(256)), { code: 'InvalidArg' });
+  t.throws(() => buf.readSizedByteArray(8), { code: 'InvalidArg' });
+
+  // Length says 4 bytes, only 2 present
+  const buf2 = new ByteBuf(Buffer.from([0x00, 0x04, 0x01, 0x02]));
+  t.throws(() => buf2.readSizedByteArray(2), { message: 'cannot readSizedByteArray, readableBytes 4 is less than 6 at readerIndex 0' });
+  t.is(buf2.getReaderIndex(), 0);
+})
+
+test('test concat', (t) => {
+  const header = new ByteBuf(Buffer.from([0x00, 0x01, 0x02]));
+  header.skipBytes(1);
+  const body = new ByteBuf(Buffer.from([0x03, 0x04, 0x05]));
+  const footer = new ByteBuf(Buffer.from([0x06]));
+
+  const buf = ByteBuf.concat([header, body, footer]);
+  t.is(buf.getReaderIndex(), 0);
+  t.is(buf.getWriterIndex(), 6);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01, 0x02, 0x03, 0x04, 0x05, 0x06]));
+
+  // Inputs are left untouched
+  t.is(header.getReaderIndex(), 1);
+  t.is(ByteBuf.concat([]).getReadableBytes(), 0);
+})
+
+test('test var int', (t) => {
+  const buf = new ByteBuf();
+  buf.writeVarInt(0);
+  buf.writeVarInt(300);
+  buf.writeVarInt(-1);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x00, 0xac, 0x02, 0xff, 0xff, 0xff, 0xff, 0x0f]));
+  t.is(buf.readVarInt(), 0);
+  t.is(buf.readVarInt(), 300);
+  t.is(buf.readVarInt(), -1);
+
+  const buf2 = new ByteBuf(Buffer.from([0x80, 0x80]));
+  t.throws(() => buf2.readVarInt(), { message: 'cannot readVarInt, readableBytes 2 is less than 3 at readerIndex 0' });
+  t.is(buf2.getReaderIndex(), 0);
+})
+
+test('test string encodings', (t) => {
+  const cases = [
+    [undefined, 1],
+    [StringEncoding.VarInt, 1],
+    [StringEncoding.UnsignedShort, 2],
+    [StringEncoding.UnsignedInt, 4],
+  ];
+  for (const [encoding, prefix] of cases) {
+    const buf = new ByteBuf();
+    buf.writeString('héllo', encoding);
+    t.is(buf.getReadableBytes(), prefix + 6);
+    t.is(buf.readString(encoding), 'héllo');
+    t.is(buf.getReadableBytes(), 0);
+  }
+
+  const buf = new ByteBuf(Buffer.from([0x02, 0xc3, 0x28]));
+  t.throws(() => buf.readString(), { message: /^cannot readString, invalid UTF-8/ });
+  t.is(buf.getReaderIndex(), 0);
+
+  const buf2 = new ByteBuf(Buffer.from([0x00, 0x05, 0x61]));
+  t.throws(() => buf2.readString(StringEncoding.UnsignedShort), { message: 'cannot readString, readableBytes 3 is less than 7 at readerIndex 0' });
+  t.is(buf2.getReaderIndex(), 0);
+})
+
+test('test to array buffer', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04]));
+  buf.skipBytes(1);
+  const readable = buf.getReadableBytes();
+  const ab = buf.toArrayBuffer();
+  t.true(ab instanceof ArrayBuffer);
+  t.is(ab.byteLength, readable);
+  t.deepEqual([...new Uint8Array(ab)], [0x02, 0x03, 0x04]);
+
+  // The backing store moved over to the ArrayBuffer
+  t.is(buf.getReadableBytes(), 0);
+  t.is(buf.getCapacity(), 0);
+  buf.writeByte(0x05);
+  new Uint8Array(ab)[0] = 0xff;
+  t.is(buf.readByte(), 0x05);
+  t.deepEqual([...new Uint8Array(ab)], [0xff, 0x03, 0x04]);
+  t.is(new ByteBuf().toArrayBuffer().byteLength, 0);
+})
+
+test('test compact', (t) => {
+  const buf = ByteBuf.withInitialCapacity(8);
+  for (let i = 1; i <= 6; i++) {
+    buf.writeByte(i);
+  }
+  buf.skipBytes(4);
+  buf.compact();
+  t.is(buf.getReaderIndex(), 0);
+  t.is(buf.getWriterIndex(), 2);
+  t.is(buf.getCapacity(), 8);
+
+  buf.writeByte(7);
+  buf.writeShort(0x0809);
+  t.is(buf.getCapacity(), 8);
+  t.deepEqual(buf.getBuffer(), Buffer.from([5, 6, 7, 8, 9]));
+  t.is(buf.readByte(), 5);
+  t.is(buf.readByte(), 6);
+})
+
+test('test date', (t) => {
+  const dates = [new Date(0), new Date('2999-12-31T23:59:59.999Z'), new Date('1901-02-03T04:05:06.007Z')];
+  const buf = new ByteBuf();
+  for (const date of dates) {
+    buf.writeDate(date);
+  }
+  t.is(buf.getReadableBytes(), 24);
+  t.deepEqual(buf.getBuffer().subarray(0, 8), Buffer.alloc(8));
+  for (const date of dates) {
+    const read = buf.readDate();
+    t.true(read instanceof Date);
+    t.is(read.getTime(), date.getTime());
+  }
+  t.true(dates[2].getTime() < 0);
+
+  t.throws(() => buf.writeDate(new Date(NaN)), { code: 'InvalidArg' });
+  t.throws(() => buf.readDate(), { message: 'cannot readDate, readableBytes 0 is less than 8 at readerIndex 24' });
+})
+
+test('test readable window', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03]));
+  buf.skipBytes(1);
+  const window = buf.readableWindow();
+  t.false(buf.isReadOnly());
+  t.true(window.isReadOnly());
+  t.is(window.getReaderIndex(), 0);
+  t.is(window.getReadableBytes(), 2);
+
+  t.throws(() => window.writeByte(1), { message: 'cannot writeByte, buffer is read-only' });
+  t.throws(() => window.writeString('a'), { message: 'cannot writeString, buffer is read-only' });
+  t.throws(() => window.compact(), { message: 'cannot compact, buffer is read-only' });
+
+  // Reading either side leaves the other alone, writes to the original don't show up in the window
+  t.is(window.readByte(), 0x02);
+  t.is(buf.getReaderIndex(), 1);
+  buf.writeByte(0x04);
+  t.is(buf.readByte(), 0x02);
+  t.is(window.getReadableBytes(), 1);
+  t.is(window.readByte(), 0x03);
+})
+
+test('test bulk numeric reads', (t) => {
+  const ints = [0, 1, -1, 0x7fffffff, -0x80000000];
+  const floats = [0, 1.5, -2.25, Infinity];
+  const doubles = [0, Math.PI, -1e300];
+  const raw = Buffer.alloc(ints.length * 4 + floats.length * 4 + doubles.length * 8);
+  let offset = 0;
+  ints.forEach((v) => (offset = raw.writeInt32BE(v, offset)));
+  floats.forEach((v) => (offset = raw.writeFloatBE(v, offset)));
+  doubles.forEach((v) => (offset = raw.writeDoubleBE(v, offset)));
+
+  const bulk = new ByteBuf(raw);
+  const single = new ByteBuf(raw);
+  const intArray = bulk.readIntArray(ints.length);
+  const floatArray = bulk.readFloatArray(floats.length);
+  const doubleArray = bulk.readDoubleArray(doubles.length);
+  t.true(intArray instanceof Int32Array);
+  t.true(floatArray instanceof Float32Array);
+  t.true(doubleArray instanceof Float64Array);
+  t.deepEqual([...intArray], ints.map(() => single.readInt()));
+  t.deepEqual([...floatArray], floats.map(() => single.readFloat()));
+  t.deepEqual([...doubleArray], doubles.map(() => single.readDouble()));
+  t.is(bulk.getReadableBytes(), 0);
+
+  const buf = new ByteBuf(Buffer.alloc(7));
+  t.throws(() => buf.readIntArray(2), { message: 'cannot readIntArray, readableBytes 7 is less than 8 at readerIndex 0' });
+  t.is(buf.getReaderIndex(), 0);
+})
+
+test('test bulk numeric writes', (t) => {
+  const ints = new Int32Array([0, 1, -1, 0x7fffffff, -0x80000000]);
+  const floats = new Float32Array([0, 1.5, -2.25, -Infinity]);
+  const doubles = new Float64Array([0, Math.E, 1e-300]);
+
+  const buf = new ByteBuf();
+  buf.writeIntArray(ints);
+  buf.writeFloatArray(floats);
+  buf.writeDoubleArray(doubles);
+  t.is(buf.getReadableBytes(), 5 * 4 + 4 * 4 + 3 * 8);
+  t.is(buf.getBuffer().readInt32BE(4), 1);
+
+  t.deepEqual(buf.readIntArray(ints.length), ints);
+  t.deepEqual(buf.readFloatArray(floats.length), floats);
+  t.deepEqual(buf.readDoubleArray(doubles.length), doubles);
+
+  const le = new ByteBuf();
+  le.setDefaultOrder(ByteOrder.LittleEndian);
+  le.writeFloatArray(floats);
+  le.writeDoubleArray(doubles);
+  t.is(le.getBuffer().readFloatLE(4), 1.5);
+  t.is(le.getBuffer().readDoubleLE(16 + 8), Math.E);
+  t.is(le.readFloat(), 0);
+  t.is(le.readFloat(), 1.5);
+  t.deepEqual([...le.readFloatArray(2)], [-2.25, -Infinity]);
+  t.deepEqual(le.readDoubleArray(doubles.length), doubles);
+})
+
+test('test writable slice', (t) => {
+  const buf = new ByteBuf();
+  buf.writeByte(0x01);
+  const slice = buf.writableSlice(3);
+  t.true(slice instanceof Uint8Array);
+  t.is(slice.length, 3);
+  t.is(buf.getWriterIndex(), 4);
+  slice.set([0x02, 0x03, 0x04]);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01, 0, 0, 0]));
+  t.throws(() => buf.commitWritableSlice(new Uint8Array(2)), {
+    code: 'InvalidArg',
+    message: 'cannot commitWritableSlice, given length 2 is not the reserved length 3',
+  });
+  buf.commitWritableSlice(slice);
+
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01, 0x02, 0x03, 0x04]));
+  t.is(buf.readByte(), 0x01);
+  t.is(buf.readByte(), 0x02);
+  t.throws(() => buf.commitWritableSlice(slice), {
+    code: 'GenericFailure',
+    message: 'cannot commitWritableSlice, no writableSlice is pending',
+  });
+})
+
+test('test read int24 signedness', (t) => {
+  const buf = new ByteBuf(Buffer.from([0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]));
+  t.is(buf.readMedium(), -1);
+  t.is(buf.readInt24Signed(), -1);
+  t.is(buf.readInt24Unsigned(), 16777215);
+  t.is(buf.readInt24SignedLE(), -1);
+  t.is(buf.readInt24UnsignedLE(), 16777215);
+
+  const buf2 = new ByteBuf(Buffer.from([0x80, 0x00, 0x00, 0x01, 0x00, 0x80, 0x7f, 0xff, 0xff]));
+  t.is(buf2.readInt24Signed(), -8388608);
+  t.is(buf2.readInt24SignedLE(), -8388607);
+  t.is(buf2.readInt24Unsigned(), 0x7fffff);
+  t.throws(() => buf2.readInt24UnsignedLE(), { message: 'cannot readInt24UnsignedLE, readableBytes 0 is less than 3 at readerIndex 9' });
+})
+
+test('test discard some read bytes', (t) => {
+  const buf = new ByteBuf(Buffer.from([0, 1, 2, 3, 4, 5, 6, 7, 8, 9]));
+
+  // Below half the capacity, nothing moves
+  buf.skipBytes(4);
+  buf.discardSomeReadBytes();
+  t.is(buf.getReaderIndex(), 4);
+  t.is(buf.getWriterIndex(), 10);
+
+  buf.skipBytes(2);
+  buf.discardSomeReadBytes();
+  t.is(buf.getReaderIndex(), 0);
+  t.is(buf.getWriterIndex(), 4);
+  t.is(buf.getCapacity(), 10);
+  t.deepEqual(buf.getBuffer(), Buffer.from([6, 7, 8, 9]));
+
+  // Fully read buffers are always reset
+  buf.skipBytes(1);
+  buf.discardSomeReadBytes();
+  t.is(buf.getReaderIndex(), 1);
+  buf.skipBytes(3);
+  buf.discardSomeReadBytes();
+  t.is(buf.getReaderIndex(), 0);
+  t.is(buf.getWriterIndex(), 0);
+})
+
+test('test enum', (t) => {
+  const buf = new ByteBuf();
+  buf.writeEnum(2, 3);
+  t.throws(() => buf.writeEnum(3, 3), { code: 'InvalidArg' });
+  t.is(buf.getWriterIndex(), 1);
+  t.is(buf.readEnum(3), 2);
+
+  buf.writeVarInt(200);
+  t.throws(() => buf.readEnum(200), {
+    code: 'OVERFLOW',
+    message: 'cannot readEnum, value 200 is not less than max 200',
+  });
+  t.is(buf.getReaderIndex(), 1);
+  t.is(buf.readEnum(201), 200);
+})
+
+test('test var int prefixed buf', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x02, 0x0a, 0x0b, 0x03, 0x0c]));
+  const frame = buf.readVarIntPrefixedBuf();
+  t.true(frame instanceof ByteBuf);
+  t.deepEqual(frame.getBuffer(), Buffer.from([0x0a, 0x0b]));
+  t.is(buf.getReaderIndex(), 3);
+
+  // Partial payload, then partial length
+  t.is(buf.readVarIntPrefixedBuf(), null);
+  t.is(buf.getReaderIndex(), 3);
+  const buf2 = new ByteBuf(Buffer.from([0x80]));
+  t.is(buf2.readVarIntPrefixedBuf(), null);
+  t.is(buf2.getReaderIndex(), 0);
+
+  buf.writeByte(0x0d);
+  buf.writeByte(0x0e);
+  t.deepEqual(buf.readVarIntPrefixedBuf().getBuffer(), Buffer.from([0x0c, 0x0d, 0x0e]));
+  t.is(buf.getReadableBytes(), 0);
+})
+
+test('test half float', (t) => {
+  const smallestSubnormal = 2 ** -24;
+  const vals = [1.0, 0.5, smallestSubnormal, Infinity, -Infinity, -2.0, 65504];
+  const buf = new ByteBuf();
+  for (const val of vals) {
+    buf.writeHalf(val);
+  }
+  buf.writeHalf(NaN);
+  t.deepEqual(buf.getBuffer().subarray(0, 8), Buffer.from([0x3c, 0x00, 0x38, 0x00, 0x00, 0x01, 0x7c, 0x00]));
+  for (const val of vals) {
+    t.is(buf.readHalf(), val);
+  }
+  t.true(Number.isNaN(buf.readHalf()));
+
+  // Out of range values overflow to infinity, tiny ones round to zero
+  buf.writeHalf(1e6);
+  buf.writeHalf(2 ** -26);
+  t.is(buf.readHalf(), Infinity);
+  t.is(buf.readHalf(), 0);
+})
+
+test('test write var int max', (t) => {
+  const buf = new ByteBuf();
+  buf.writeVarIntMax(127, 1);
+  buf.writeVarIntMax(2097151, 3);
+  t.is(buf.getWriterIndex(), 4);
+  t.throws(() => buf.writeVarIntMax(128, 1), {
+    message: 'cannot writeVarIntMax, given value 128 takes 2 bytes, more than maxBytes 1',
+  });
+  t.throws(() => buf.writeVarIntMax(-1, 4), { code: 'InvalidArg' });
+  t.is(buf.getWriterIndex(), 4);
+  t.is(buf.readVarInt(), 127);
+  t.is(buf.readVarInt(), 2097151);
+})
+
+test('test debug assert ascii', (t) => {
+  const buf = new ByteBuf(Buffer.from('xGET /\r\n'));
+  buf.skipBytes(1);
+  t.notThrows(() => buf.debugAssertAscii(5));
+  t.is(buf.getReaderIndex(), 1);
+  t.throws(() => buf.debugAssertAscii(6), {
+    message: 'cannot debugAssertAscii, byte 0x0d at index 6 is not printable ASCII',
+  });
+  t.throws(() => buf.debugAssertAscii(8), { message: 'cannot debugAssertAscii, readableBytes 7 is less than 8 at readerIndex 1' });
+})
+
+test('test utf16 string', (t) => {
+  for (const littleEndian of [true, false]) {
+    const buf = new ByteBuf();
+    buf.writeUtf16String('héllo', littleEndian);
+    buf.writeUtf16String('a😀', littleEndian);
+    t.is(buf.getReadableBytes(), 12 + 8);
+    const expected = Buffer.from('héllo\0', 'utf16le');
+    t.deepEqual(buf.getBuffer().subarray(0, 12), littleEndian ? expected : expected.swap16());
+    t.is(buf.readUtf16String(littleEndian), 'héllo');
+    t.is(buf.readUtf16String(littleEndian), 'a😀');
+  }
+
+  // Missing terminator
+  const buf = new ByteBuf(Buffer.from('abc', 'utf16le'));
+  t.throws(() => buf.readUtf16String(true), { message: 'cannot readUtf16String, no terminator in the readable bytes' });
+  t.is(buf.getReaderIndex(), 0);
+
+  // Lone high surrogate
+  const buf2 = new ByteBuf(Buffer.from([0x3d, 0xd8, 0x41, 0x00, 0x00, 0x00]));
+  t.throws(() => buf2.readUtf16String(true), { message: 'cannot readUtf16String, invalid UTF-16: lone surrogate' });
+})
+
+test('test indices', (t) => {
+  const buf = ByteBuf.withInitialCapacity(8);
+  buf.writeShort(1);
+  buf.writeShort(2);
+  buf.skipBytes(1);
+  t.deepEqual(buf.indices(), {
+    readerIndex: buf.getReaderIndex(),
+    writerIndex: buf.getWriterIndex(),
+    readableBytes: buf.getReadableBytes(),
+    capacity: buf.getCapacity(),
+  });
+  t.deepEqual(buf.indices(), { readerIndex: 1, writerIndex: 4, readableBytes: 3, capacity: 8 });
+})
+
+test('test var long array', (t) => {
+  const vals = [9223372036854775807n, 0n, -42n, 300n];
+  const buf = new ByteBuf();
+  buf.writeVarLongArray(vals);
+  // count + 9 + 1 + 10 + 2 bytes
+  t.is(buf.getReadableBytes(), 1 + 9 + 1 + 10 + 2);
+  t.deepEqual(buf.readVarLongArray(4), vals);
+
+  buf.writeVarLongArray(vals);
+  t.throws(() => buf.readVarLongArray(3), {
+    code: 'OVERFLOW',
+    message: 'cannot readVarLongArray, count 4 is greater than max 3',
+  });
+  t.is(buf.getReaderIndex(), 23);
+
+  t.throws(() => buf.writeVarLongArray([1n, 2n ** 64n]), { code: 'InvalidArg' });
+  t.is(buf.getWriterIndex(), 46);
+
+  const empty = new ByteBuf();
+  empty.writeVarLongArray([]);
+  t.deepEqual(empty.readVarLongArray(0), []);
+})
+
+test('test write transaction', (t) => {
+  const buf = new ByteBuf();
+  buf.writeByte(0x01);
+
+  const err = t.throws(() =>
+    buf.writeTransaction(() => {
+      buf.writeShort(0x0203);
+      throw new Error('serialization failed');
+    })
+  );
+  t.is(err.message, 'serialization failed');
+  t.is(buf.getWriterIndex(), 1);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01]));
+
+  buf.writeTransaction(() => {
+    buf.writeShort(0x0203);
+  });
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01, 0x02, 0x03]));
+})
+
+test('test write transaction rejects moving the indices by hand', (t) => {
+  const buf = new ByteBuf();
+  buf.writeByte(0x01);
+  buf.writeShort(0x0203);
+  buf.readByte();
+
+  for (const method of ['clear', 'compact']) {
+    t.throws(
+      () =>
+        buf.writeTransaction(() => {
+          buf.writeByte(0x04);
+          buf.readByte();
+          buf[method]();
+          throw new Error('unreachable');
+        }),
+      { message: `cannot ${method}, buffer is in use by writeTransaction` },
+    );
+    t.is(buf.getReaderIndex(), 1);
+    t.is(buf.getWriterIndex(), 3);
+    t.is(buf.readShort(), 0x0203);
+    buf.setReaderIndex(1);
+  }
+
+  buf.writeTransaction(() => {
+    buf.writeTransaction(() => buf.writeByte(0x04));
+    t.throws(() => buf.setWriterIndex(0), {
+      message: 'cannot setWriterIndex, buffer is in use by writeTransaction',
+    });
+  });
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x02, 0x03, 0x04]));
+  buf.clear();
+})
+
+test('test write after lowering writer index', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04]));
+  buf.setWriterIndex(0);
+  buf.writeByte(0x09);
+  t.is(buf.readByte(), 0x09);
+  t.is(buf.getWriterIndex(), 1);
+  buf.setIndex(0, 4);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x09, 0x02, 0x03, 0x04]));
+
+  buf.setWriterIndex(2);
+  t.throws(() =>
+    buf.writeTransaction(() => {
+      buf.writeShort(0x0506);
+      throw new Error('serialization failed');
+    })
+  );
+  t.is(buf.getWriterIndex(), 2);
+  buf.setWriterIndex(4);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x09, 0x02, 0x03, 0x04]));
+})
+
+test('test read remaining', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04]));
+  buf.skipBytes(1);
+  const readable = buf.getReadableBytes();
+  const rest = buf.readRemaining();
+  t.is(rest.length, readable);
+  t.deepEqual(rest, Buffer.from([0x02, 0x03, 0x04]));
+  t.is(buf.getReadableBytes(), 0);
+  t.is(buf.readRemaining().length, 0);
+})
+
+test('test on grow', (t) => {
+  const buf = ByteBuf.withInitialCapacity(2);
+  const events = [];
+  buf.onGrow((oldCapacity, newCapacity) => events.push([oldCapacity, newCapacity]));
+  buf.writeShort(1);
+  t.is(events.length, 0);
+  for (let i = 0; i < 64; i++) {
+    buf.writeByte(i);
+  }
+  t.true(events.length > 0);
+  t.deepEqual(events[0][0], 2);
+  for (const [oldCapacity, newCapacity] of events) {
+    t.true(newCapacity > oldCapacity);
+  }
+  t.is(events[events.length - 1][1], buf.getCapacity());
+
+  buf.onGrow(null);
+  const count = events.length;
+  buf.writeIntArray(new Int32Array(256));
+  t.is(events.length, count);
+
+  const reentrant = ByteBuf.withInitialCapacity(1);
+  reentrant.onGrow(() => reentrant.writeByte(0));
+  t.throws(() => reentrant.writeShort(1), {
+    message: 'cannot writeByte, buffer is in use by onGrow',
+  });
+  reentrant.onGrow(() => reentrant.onGrow(null));
+  t.throws(() => reentrant.writeIntArray(new Int32Array(16)), {
+    message: 'cannot onGrow, buffer is in use by onGrow',
+  });
+  t.is(reentrant.getWriterIndex(), 0);
+  reentrant.onGrow(null);
+  reentrant.writeByte(0x7f);
+  t.is(reentrant.getWriterIndex(), 1);
+
+  const rejected = ByteBuf.withInitialCapacity(1);
+  rejected.onGrow(() => {
+    throw new Error('grow rejected');
+  });
+  rejected.writeByte(0x7f);
+  t.throws(() => rejected.writeInt(0x01020304), { message: 'grow rejected' });
+  t.is(rejected.getWriterIndex(), 1);
+  t.deepEqual(rejected.getBuffer(), Buffer.from([0x7f]));
+  t.throws(() => rejected.writableSlice(1024), { message: 'grow rejected' });
+  t.is(rejected.getWriterIndex(), 1);
+  rejected.onGrow(null);
+  rejected.writeInt(0x01020304);
+  t.deepEqual(rejected.getBuffer(), Buffer.from([0x7f, 0x01, 0x02, 0x03, 0x04]));
+})
+
+test('test write medium', (t) => {
+  const buf = new ByteBuf();
+  buf.writeByte(0x01);
+  buf.writeMedium(-8388607);
+  buf.writeMedium(0x123456);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01, 0x80, 0x00, 0x01, 0x12, 0x34, 0x56]));
+  t.is(buf.readByte(), 0x01);
+  t.is(buf.readMedium(), -8388607);
+  t.is(buf.readMedium(), 0x123456);
+})
+
+test('test read scalar', (t) => {
+  const raw = Buffer.alloc(1 + 2 + 4 + 8 + 4 + 8);
+  let offset = raw.writeInt8(-5, 0);
+  offset = raw.writeInt16BE(-300, offset);
+  offset = raw.writeInt32BE(123456, offset);
+  offset = raw.writeBigInt64BE(-9007199254740993n, offset);
+  offset = raw.writeFloatBE(1.5, offset);
+  raw.writeDoubleBE(Math.PI, offset);
+  const buf = new ByteBuf(Buffer.concat([raw, Buffer.from([0xac, 0x02, 0x01])]));
+
+  const record = ['byte', 'short', 'int', 'long', 'float', 'double', 'varint', 'bool'].map((type) => buf.readScalar(type));
+  t.deepEqual(record, [-5, -300, 123456, -9007199254740993n, 1.5, Math.PI, 300, true]);
+  t.is(buf.getReadableBytes(), 0);
+
+  t.throws(() => buf.readScalar('uuid'), { message: 'cannot readScalar, unknown type name uuid' });
+  t.throws(() => buf.readScalar('int'), { message: 'cannot readInt, readableBytes 0 is less than 4 at readerIndex 30' });
+})
+
+test('test write scalar', (t) => {
+  const types = ['byte', 'short', 'int', 'long', 'float', 'double', 'varint', 'bool'];
+  const values = [-5, -300, 123456, -9007199254740993n, 1.5, Math.PI, 300, true];
+  const buf = new ByteBuf();
+  types.forEach((type, i) => buf.writeScalar(type, values[i]));
+  t.is(buf.getReadableBytes(), 1 + 2 + 4 + 8 + 4 + 8 + 2 + 1);
+  t.deepEqual(types.map((type) => buf.readScalar(type)), values);
+
+  t.throws(() => buf.writeScalar('int', 1n), { message: 'cannot writeScalar, expected Number for int but got BigInt' });
+  t.throws(() => buf.writeScalar('long', 1), { code: 'InvalidArg' });
+  t.throws(() => buf.writeScalar('bool', 'yes'), { code: 'InvalidArg' });
+  t.throws(() => buf.writeScalar('uuid', 1), { message: 'cannot writeScalar, unknown type name uuid' });
+  t.is(buf.getWriterIndex(), 30);
+})
+
+test('test to utf8 string', (t) => {
+  const buf = new ByteBuf(Buffer.from('xhéllo wörld'));
+  buf.skipBytes(1);
+  t.is(buf.toUtf8String(), 'héllo wörld');
+  t.is(buf.getReaderIndex(), 1);
+
+  const buf2 = new ByteBuf(Buffer.from([0x61, 0xff]));
+  t.throws(() => buf2.toUtf8String(), { message: /^cannot toUtf8String, invalid UTF-8/ });
+})
+
+test('test little endian longs', (t) => {
+  const min = -(2n ** 63n);
+  const max = 2n ** 64n - 1n;
+  const buf = new ByteBuf();
+  buf.writeLongLE(min);
+  buf.writeUnsignedLongLE(max);
+  buf.writeUnsignedLongLE(0x0102030405060708n);
+  buf.writeUnsignedLong(0x0102030405060708n);
+
+  const bytes = [...buf.getArray()];
+  t.deepEqual(bytes.slice(0, 8), [0, 0, 0, 0, 0, 0, 0, 0x80]);
+  t.deepEqual(bytes.slice(8, 16), Array(8).fill(0xff));
+  t.deepEqual(bytes.slice(16, 24), [8, 7, 6, 5, 4, 3, 2, 1]);
+  t.deepEqual(bytes.slice(24, 32), [1, 2, 3, 4, 5, 6, 7, 8]);
+
+  t.is(buf.readLongLE(), min);
+  t.is(buf.readUnsignedLongLE(), max);
+  t.is(buf.readLongLE(), 0x0102030405060708n);
+  t.is(buf.readUnsignedLong(), 0x0102030405060708n);
+
+  t.throws(() => buf.writeUnsignedLongLE(-1n), { code: 'InvalidArg' });
+  t.throws(() => buf.writeLongLE(2n ** 63n), { code: 'InvalidArg' });
+  t.throws(() => buf.readLongLE(), { message: 'cannot readLongLE, readableBytes 0 is less than 8 at readerIndex 32' });
+})
+
+test('test cursor', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x00, 0x01, 0x02, 0x03, 0x04, 0x05]));
+  buf.skipBytes(1);
+  const a = buf.cursor();
+  const b = buf.cursor();
+  t.true(a instanceof ByteBufCursor);
+  t.is(a.getPosition(), 1);
+
+  b.setPosition(3);
+  t.is(a.readByte(), 0x01);
+  t.is(b.readShort(), 0x0304);
+  t.is(a.readShort(), 0x0203);
+  t.is(b.getReadableBytes(), 1);
+  t.is(a.getPosition(), 4);
+  t.is(buf.getReaderIndex(), 1);
+  t.is(buf.readByte(), 0x01);
+  t.is(a.readByte(), 0x04);
+})
+
+test('test read into uint8array', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04]));
+  buf.skipBytes(1);
+  const arr = buf.readIntoUint8Array(2);
+  t.true(arr instanceof Uint8Array);
+  t.false(Buffer.isBuffer(arr));
+  t.deepEqual([...arr], [0x02, 0x03]);
+  t.is(buf.getReaderIndex(), 3);
+  t.throws(() => buf.readIntoUint8Array(2), { message: 'cannot readIntoUint8Array, readableBytes 1 is less than 2 at readerIndex 3' });
+})
+
+test('test swap endianness', (t) => {
+  const ints = [1, -2, 0x01020304];
+  const buf = new ByteBuf();
+  buf.writeByte(0xff);
+  buf.writeIntArray(new Int32Array(ints));
+  buf.swapIntEndianness(1, 3);
+  buf.skipBytes(1);
+  const copy = buf.getBuffer();
+  t.deepEqual(ints.map((_, i) => copy.readInt32LE(i * 4)), ints);
+
+  const buf2 = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]));
+  buf2.swapShortEndianness(2, 2);
+  t.deepEqual(buf2.getBuffer(), Buffer.from([0x01, 0x02, 0x04, 0x03, 0x06, 0x05, 0x07, 0x08]));
+  buf2.swapLongEndianness(0, 1);
+  t.deepEqual(buf2.getBuffer(), Buffer.from([0x08, 0x07, 0x05, 0x06, 0x03, 0x04, 0x02, 0x01]));
+  t.throws(() => buf2.swapIntEndianness(1, 2), { message: 'cannot swapIntEndianness, given range 1..9 exceeds writerIndex 8' });
+})
+
+test('test read hex', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0xde, 0xad, 0xbe, 0xef, 0x02]));
+  buf.skipBytes(1);
+  t.is(buf.readHex(4), 'deadbeef');
+  t.is(buf.getReaderIndex(), 5);
+  t.is(buf.readHex(0), '');
+  t.throws(() => buf.readHex(2), { message: 'cannot readHex, readableBytes 1 is less than 2 at readerIndex 5' });
+})
+
+test('test error codes', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01]));
+  t.throws(() => buf.readInt(), { code: 'UNDERFLOW', message: 'cannot readInt, readableBytes 1 is less than 4 at readerIndex 0' });
+  t.is(buf.getReaderIndex(), 0);
+
+  const str = new ByteBuf();
+  str.writeVarInt(2);
+  str.writeByte(0xc3);
+  str.writeByte(0x28);
+  t.throws(() => str.readString(), { code: 'INVALID_UTF8' });
+  t.throws(() => new ByteBuf(Buffer.from([0xff])).toUtf8String(), { code: 'INVALID_UTF8' });
+
+  const varInt = new ByteBuf(Buffer.from([0xff, 0xff, 0xff, 0xff, 0xff, 0x01]));
+  t.throws(() => varInt.readVarInt(), { code: 'OVERFLOW' });
+})
+
+test('test skip up to', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04, 0x05]));
+  t.is(buf.skipUpTo(2), 2);
+  t.is(buf.getReaderIndex(), 2);
+  t.is(buf.skipUpTo(10), 3);
+  t.is(buf.getReaderIndex(), 5);
+  t.is(buf.skipUpTo(1), 0);
+})
+
+test('test write string capped', (t) => {
+  const buf = new ByteBuf();
+  t.is(buf.writeStringCapped('héllo', 6), 7);
+  t.is(buf.readString(), 'héllo');
+
+  t.throws(() => buf.writeStringCapped('héllo', 5), {
+    code: 'OVERFLOW',
+    message: 'cannot writeStringCapped, string length 6 is greater than maxBytes 5',
+  });
+  t.is(buf.getWriterIndex(), 7);
+})
+
+test('test decode frames', (t) => {
+  const buf = new ByteBuf();
+  buf.writeVarInt(2);
+  buf.writeShort(0x0102);
+  buf.writeVarInt(1);
+  buf.writeByte(0x03);
+  buf.writeVarInt(4);
+  buf.writeShort(0x0405);
+
+  const frames = [];
+  t.is(buf.decodeFrames((frame) => frames.push(frame.getBuffer())), 2);
+  t.deepEqual(frames, [Buffer.from([0x01, 0x02]), Buffer.from([0x03])]);
+  t.is(buf.getReaderIndex(), 5);
+
+  buf.writeShort(0x0607);
+  t.is(buf.decodeFrames((frame) => frames.push(frame.getBuffer())), 1);
+  t.deepEqual(frames[2], Buffer.from([0x04, 0x05, 0x06, 0x07]));
+  t.is(buf.getReadableBytes(), 0);
+
+  buf.writeVarInt(1);
+  buf.writeByte(0x08);
+  t.throws(() => buf.decodeFrames(() => buf.readByte()), {
+    message: 'cannot readByte, buffer is in use by decodeFrames',
+  });
+  t.is(buf.getReadableBytes(), 0);
+  buf.writeByte(0x09);
+  t.is(buf.readByte(), 0x09);
+})
+
+test('test max frame length', (t) => {
+  const buf = new ByteBuf();
+  buf.writeVarInt(3);
+  buf.writeByte(0x01);
+  buf.writeShort(0x0203);
+  t.deepEqual(buf.readVarIntPrefixedBuf(3).getBuffer(), Buffer.from([0x01, 0x02, 0x03]));
+
+  // only the prefix has arrived, the cap must fire without waiting for the body
+  buf.writeVarInt(1000);
+  t.throws(() => buf.readVarIntPrefixedBuf(999), {
+    code: 'OVERFLOW',
+    message: 'cannot readVarIntPrefixedBuf, length 1000 is greater than maxFrameLength 999',
+  });
+  t.is(buf.getReaderIndex(), 4);
+  t.throws(() => buf.decodeFrames(() => {}, 16), { code: 'OVERFLOW' });
+  t.is(buf.readVarIntPrefixedBuf(1000), null);
+})
+
+test('test bits LE', (t) => {
+  const le = new ByteBuf();
+  le.writeBitsLE(0b10110, 5);
+  le.writeBitsLE(0b011, 3);
+  le.writeBitsLE(0x1ff, 9);
+  t.deepEqual(le.getBuffer(), Buffer.from([0x76, 0xff, 0x01]));
+  t.is(le.readBitsLE(5), 0b10110);
+  t.is(le.readBitsLE(3), 0b011);
+  t.is(le.readBitsLE(9), 0x1ff);
+
+  const msb = new ByteBuf();
+  msb.writeBits(0b10110, 5);
+  msb.writeBits(0b011, 3);
+  t.deepEqual(msb.getBuffer(), Buffer.from([0xb3]));
+  t.is(msb.readBits(5), 0b10110);
+  t.is(msb.readBits(3), 0b011);
+
+  // byte access after a partial bit access moves on to the next whole byte
+  const mixed = new ByteBuf();
+  mixed.writeBits(1, 1);
+  mixed.writeByte(0x7f);
+  mixed.writeBits(0xffffffff, 32);
+  t.deepEqual(mixed.getBuffer(), Buffer.from([0x80, 0x7f, 0xff, 0xff, 0xff, 0xff]));
+  t.is(mixed.readBits(1), 1);
+  t.is(mixed.readByte(), 0x7f);
+  t.is(mixed.readBits(32), 0xffffffff);
+
+  t.throws(() => le.readBitsLE(8), { code: 'UNDERFLOW', message: 'cannot readBitsLE, readable bits 7 are less than 8 at readerIndex 3' });
+  t.is(le.readBitsLE(7), 0);
+  t.throws(() => le.writeBitsLE(4, 2), { code: 'InvalidArg' });
+  t.throws(() => le.writeBits(0, 33), { code: 'InvalidArg' });
+})
+
+test('test to full buffer', (t) => {
+  const buf = new ByteBuf();
+  buf.writeShort(0x0102);
+  buf.writeByte(0x03);
+  buf.readShort();
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x03]));
+  t.deepEqual(buf.toFullBuffer(), Buffer.from([0x01, 0x02, 0x03]));
+})
+
+test('test auto grow', (t) => {
+  const buf = ByteBuf.withInitialCapacity(2);
+  buf.writeByte(0x01);
+  t.throws(() => buf.setWriterIndex(8), { code: 'InvalidArg' });
+
+  buf.setAutoGrow(true);
+  buf.setWriterIndex(8);
+  t.is(buf.getWriterIndex(), 8);
+  t.true(buf.getCapacity() >= 8);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01, 0, 0, 0, 0, 0, 0, 0]));
+  buf.writeByte(0x02);
+  t.deepEqual(buf.toFullBuffer().subarray(8), Buffer.from([0x02]));
+
+  buf.setIndex(9, 20);
+  t.true(buf.getCapacity() >= 20);
+  t.deepEqual(buf.getBuffer(), Buffer.alloc(11));
+
+  buf.setAutoGrow(false);
+  t.throws(() => buf.setWriterIndex(buf.getCapacity() + 1), { code: 'InvalidArg' });
+  t.throws(() => buf.setIndex(0, buf.getCapacity() + 1), { code: 'InvalidArg' });
+})
+
+test('test detailed underflow message', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03]));
+  buf.readShort();
+  t.throws(() => buf.readLong(), {
+    code: 'UNDERFLOW',
+    message: 'cannot readLong, readableBytes 1 is less than 8 at readerIndex 2',
+  });
+
+  // multi-step reads report against the reader index they restore to
+  const str = new ByteBuf();
+  str.writeVarInt(10);
+  str.writeByte(0x61);
+  t.throws(() => str.readString(), { message: 'cannot readString, readableBytes 2 is less than 11 at readerIndex 0' });
+})
+
+test('test string list', (t) => {
+  const buf = new ByteBuf();
+  buf.writeStringList([]);
+  t.deepEqual(buf.readStringList(0), []);
+
+  const list = ['minecraft:stone', 'héllo', '日本語', ''];
+  buf.writeStringList(list);
+  t.deepEqual(buf.readStringList(4), list);
+
+  buf.writeStringList(list);
+  t.throws(() => buf.readStringList(3), {
+    code: 'OVERFLOW',
+    message: 'cannot readStringList, count 4 is greater than maxCount 3',
+  });
+  t.deepEqual(buf.readStringList(4), list);
+})
+
+test('test align', (t) => {
+  const buf = new ByteBuf();
+  buf.writeByte(0x01);
+  buf.alignWriter(4);
+  t.is(buf.getWriterIndex(), 4);
+  buf.alignWriter(4);
+  t.is(buf.getWriterIndex(), 4);
+  buf.writeShort(0x0203);
+  buf.alignWriter(8);
+  t.is(buf.getWriterIndex(), 8);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01, 0, 0, 0, 0x02, 0x03, 0, 0]));
+
+  buf.readByte();
+  buf.alignReader(4);
+  t.is(buf.getReaderIndex(), 4);
+  t.is(buf.readShort(), 0x0203);
+  buf.alignReader(8);
+  t.is(buf.getReaderIndex(), 8);
+
+  buf.writeByte(0x04);
+  buf.readByte();
+  t.throws(() => buf.alignReader(4), { code: 'UNDERFLOW' });
+  t.throws(() => buf.alignReader(0), { code: 'InvalidArg', message: 'cannot alignReader, given boundary is 0' });
+  t.throws(() => buf.alignWriter(0), { code: 'InvalidArg' });
+})
+
+test('test peek at', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x00, 0x01, 0x02, 0x03, 0xff]));
+  buf.skipBytes(1);
+  t.is(buf.peekAt(0), 0x01);
+  t.is(buf.peekAt(3), 0xff);
+  t.is(buf.getReaderIndex(), 1);
+  t.throws(() => buf.peekAt(4), {
+    code: 'UNDERFLOW',
+    message: 'cannot peekAt, readableBytes 4 is less than 5 at readerIndex 1',
+  });
+})
+
+test('test fill', (t) => {
+  const buf = new ByteBuf();
+  buf.writeByte(0xff);
+  buf.fill(4, (i) => i + 1);
+  t.deepEqual(buf.getArray(), new Uint8Array([0xff, 0x01, 0x02, 0x03, 0x04]));
+
+  t.throws(() =>
+    buf.fill(3, (i) => {
+      if (i === 2) throw new Error('producer failed');
+      return i;
+    }), { message: 'producer failed' }
+  );
+  t.is(buf.getWriterIndex(), 5);
+
+  t.throws(() => buf.fill(2, () => buf.getWriterIndex()), {
+    message: 'cannot getWriterIndex, buffer is in use by fill',
+  });
+  t.throws(() => buf.fill(1, () => ({ valueOf: () => buf.readByte() })), {
+    message: 'cannot readByte, buffer is in use by fill',
+  });
+  t.is(buf.getWriterIndex(), 5);
+  t.is(buf.readByte(), -1);
+})
+
+test('test hash code', (t) => {
+  const a = ByteBuf.withInitialCapacity(64);
+  a.writeByte(0x00);
+  a.writeInt(0x01020304);
+  a.readByte();
+
+  const b = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04]));
+  t.not(a.getCapacity(), b.getCapacity());
+  t.is(a.hashCode(), b.hashCode());
+
+  b.readByte();
+  t.not(a.hashCode(), b.hashCode());
+  // FNV-1a offset basis for no bytes
+  t.is(new ByteBuf().hashCode(), 0x811c9dc5 | 0);
+})
+
+test('test read bytes reversed', (t) => {
+  const buf = new ByteBuf(Buffer.from([0xaa, 0x01, 0x02, 0x03, 0x04, 0xbb]));
+  buf.skipBytes(1);
+  t.deepEqual(buf.readBytesReversed(4), Buffer.from([0x04, 0x03, 0x02, 0x01]));
+  t.is(buf.getReaderIndex(), 5);
+  t.throws(() => buf.readBytesReversed(2), { code: 'UNDERFLOW' });
+})
+
+test('test strict index setters', (t) => {
+  const buf = ByteBuf.withInitialCapacity(16);
+  buf.writeInt(0x01020304);
+  buf.setWriterIndex(2);
+  buf.setWriterIndexStrict(4);
+  t.is(buf.getWriterIndex(), 4);
+  buf.setReaderIndexStrict(4);
+  t.is(buf.getReaderIndex(), 4);
+  buf.setReaderIndexStrict(0);
+
+  t.throws(() => buf.setWriterIndexStrict(8), {
+    code: 'InvalidArg',
+    message: 'cannot setWriterIndexStrict, given writerIndex 8 is greater than written length 4',
+  });
+  buf.setWriterIndex(8);
+  buf.setReaderIndexStrict(6);
+  t.is(buf.readShort(), 0);
+})
+
+test('test set writer index zero-fills reserved capacity', (t) => {
+  for (const autoGrow of [false, true]) {
+    const buf = ByteBuf.withInitialCapacity(16);
+    buf.setAutoGrow(autoGrow);
+    buf.writeInt(0x01020304);
+    buf.setWriterIndex(10);
+    t.deepEqual(buf.getBuffer(), Buffer.from([0x01, 0x02, 0x03, 0x04, 0, 0, 0, 0, 0, 0]));
+    t.is(buf.cursor().getReadableBytes(), 10);
+    t.true(buf.inspect().length > 0);
+    buf.setIndex(12, 14);
+    t.is(buf.readShort(), 0);
+    buf.setIndex(4, 16);
+    buf.compact();
+    t.is(buf.getWriterIndex(), 12);
+    t.deepEqual(buf.getBuffer(), Buffer.alloc(12));
+    buf.setCapacity(2);
+    t.is(buf.getWriterIndex(), 2);
+    t.deepEqual(buf.getBuffer(), Buffer.alloc(2));
+  }
+})
+
+test('test zero on clear', (t) => {
+  const buf = ByteBuf.withInitialCapacity(16);
+  buf.setZeroOnClear(true);
+  buf.writeInt(0x5ec12e7);
+  buf.clear();
+  t.is(buf.getWriterIndex(), 0);
+  t.is(buf.getCapacity(), 16);
+
+  buf.writeShort(0x0102);
+  t.is(buf.readShort(), 0x0102);
+})
+
+test('test allocate', (t) => {
+  const buf = ByteBuf.allocate(16);
+  t.is(buf.getReadableBytes(), 16);
+  t.is(buf.getReaderIndex(), 0);
+  t.is(buf.getWriterIndex(), 16);
+  buf.setInt(12, 0x01020304);
+  t.is(buf.getWriterIndex(), 16);
+  t.deepEqual(buf.getBuffer(), Buffer.from([...Array(12).fill(0), 0x01, 0x02, 0x03, 0x04]));
+  t.throws(() => buf.setInt(13, 1), {
+    code: 'InvalidArg',
+    message: 'cannot setInt, given range 13..17 exceeds writerIndex 16',
+  });
+})
+
+test('test read var int size', (t) => {
+  const buf = new ByteBuf();
+  buf.writeVarInt(1);
+  buf.writeVarInt(-1);
+  t.is(buf.readVarIntSize(), 1);
+  t.is(buf.getReaderIndex(), 0);
+  buf.readVarInt();
+  t.is(buf.readVarIntSize(), 5);
+  buf.readVarInt();
+
+  buf.writeByte(0x80);
+  t.throws(() => buf.readVarIntSize(), { code: 'UNDERFLOW' });
+})
+
+test('test copy to array buffer', (t) => {
+  const buf = new ByteBuf(Buffer.from([0xff, 0x01, 0x02, 0x03]));
+  buf.readByte();
+  const target = new ArrayBuffer(8);
+  t.is(buf.copyToArrayBuffer(target, 4), 3);
+  t.deepEqual(new Uint8Array(target), new Uint8Array([0, 0, 0, 0, 0x01, 0x02, 0x03, 0]));
+  t.is(buf.getReadableBytes(), 3);
+  t.throws(() => buf.copyToArrayBuffer(target, 6), {
+    code: 'InvalidArg',
+    message: 'cannot copyToArrayBuffer, given offset 6 + readableBytes 3 exceeds byteLength 8',
+  });
+})
+
+test('test max capacity', (t) => {
+  const buf = new ByteBuf();
+  t.is(buf.getMaxCapacity(), null);
+  t.false(buf.isFull());
+
+  buf.setMaxCapacity(6);
+  t.is(buf.getMaxCapacity(), 6);
+  buf.writeInt(1);
+  t.false(buf.isFull());
+  buf.writeShort(2);
+  t.true(buf.isFull());
+
+  t.throws(() => buf.writeByte(3), {
+    code: 'OVERFLOW',
+    message: 'cannot write 1 bytes, writerIndex 6 would exceed maxCapacity 6',
+  });
+  t.is(buf.getWriterIndex(), 6);
+  t.throws(() => buf.setMaxCapacity(4), { code: 'InvalidArg' });
+
+  buf.setMaxCapacity(null);
+  buf.writeByte(3);
+  t.false(buf.isFull());
+})
+
+test('test read string to end', (t) => {
+  const utf8 = new ByteBuf(Buffer.from('héllo 日本', 'utf8'));
+  t.is(utf8.readStringToEnd('utf-8'), 'héllo 日本');
+  t.is(utf8.getReadableBytes(), 0);
+  t.is(utf8.readStringToEnd('utf8'), '');
+
+  const ascii = new ByteBuf(Buffer.from('hello', 'ascii'));
+  t.is(ascii.readStringToEnd('ascii'), 'hello');
+
+  const latin1 = new ByteBuf(Buffer.from([0x63, 0x61, 0x66, 0xe9]));
+  t.is(latin1.readStringToEnd('latin1'), 'café');
+
+  const high = new ByteBuf(Buffer.from([0x61, 0xe9]));
+  t.throws(() => high.readStringToEnd('ascii'), { message: 'cannot readStringToEnd, byte 0xe9 at index 1 is not ASCII' });
+  t.is(high.getReaderIndex(), 0);
+  t.throws(() => high.readStringToEnd('utf-8'), { code: 'INVALID_UTF8' });
+  t.throws(() => high.readStringToEnd('utf-16'), { code: 'InvalidArg' });
+})
+
+test('test split', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04]));
+  const [header, body] = buf.split(1);
+  t.deepEqual(header.getBuffer(), Buffer.from([0x01]));
+  t.deepEqual(body.getBuffer(), Buffer.from([0x02, 0x03, 0x04]));
+  body.writeByte(0x05);
+  t.is(buf.getWriterIndex(), 4);
+
+  const [empty, all] = buf.split(0);
+  t.is(empty.getReadableBytes(), 0);
+  t.deepEqual(all.getBuffer(), buf.getBuffer());
+  const [whole, rest] = buf.split(4);
+  t.deepEqual(whole.getBuffer(), buf.getBuffer());
+  t.is(rest.getReadableBytes(), 0);
+
+  t.throws(() => buf.split(5), { code: 'InvalidArg', message: 'cannot split, given index 5 is greater than writerIndex 4' });
+})
+
+test('test var int bytes written', (t) => {
+  const buf = new ByteBuf();
+  t.is(buf.writeVarInt(1), 1);
+  t.is(buf.writeVarInt(300), 2);
+  t.is(buf.writeVarInt(-1), 5);
+  t.is(buf.writeVarLong(1n), 1);
+  t.is(buf.writeVarLong(-1n), 10);
+  t.is(buf.getWriterIndex(), 19);
+})
+
+test('test default order floats', (t) => {
+  const buf = new ByteBuf();
+  t.is(buf.getDefaultOrder(), ByteOrder.BigEndian);
+  buf.writeFloat(1.5);
+  buf.writeDouble(-2.25);
+  const be = buf.toFullBuffer();
+  t.is(be.readFloatBE(0), 1.5);
+  t.is(buf.getFloat(0), 1.5);
+  t.is(buf.getDouble(4), -2.25);
+
+  buf.setDefaultOrder(ByteOrder.LittleEndian);
+  t.is(buf.getDefaultOrder(), ByteOrder.LittleEndian);
+  const leFloat = be.readFloatLE(0);
+  const leDouble = be.readDoubleLE(4);
+  t.is(buf.getFloat(0), leFloat);
+  t.is(buf.getDouble(4), leDouble);
+  t.is(buf.readFloat(), leFloat);
+  t.is(buf.readDouble(), leDouble);
+
+  buf.writeFloat(1.5);
+  t.is(buf.toFullBuffer().readFloatLE(12), 1.5);
+  t.is(buf.readFloat(), 1.5);
+  t.throws(() => buf.getDouble(10), { code: 'InvalidArg', message: 'cannot getDouble, given range 10..18 exceeds writerIndex 16' });
+})
+
+test('test read batch', (t) => {
+  const buf = new ByteBuf();
+  buf.writeInt(-7);
+  buf.writeShort(300);
+  buf.writeVarInt(150);
+  buf.writeLong(1n << 40n);
+  buf.writeBoolean(true);
+  const copy = new ByteBuf(buf.getBuffer());
+
+  const spec = ['int', 'short', 'varint', 'long', 'bool'];
+  t.deepEqual(buf.readBatch(spec), [copy.readInt(), copy.readShort(), copy.readVarInt(), copy.readLong(), copy.readBoolean()]);
+  t.is(buf.getReadableBytes(), 0);
+
+  buf.writeInt(1);
+  t.throws(() => buf.readBatch(['int', 'byte']), { code: 'UNDERFLOW' });
+  t.is(buf.getReadableBytes(), 4);
+})
+
+test('test write batch', (t) => {
+  const spec = ['int', 'short', 'varint', 'long', 'bool', 'double'];
+  const record = [-7, 300, 150, 1n << 40n, true, 0.5];
+  const buf = new ByteBuf();
+  buf.writeBatch(spec, record);
+  t.deepEqual(buf.readBatch(spec), record);
+
+  t.throws(() => buf.writeBatch(['int', 'short'], [1]), {
+    code: 'InvalidArg',
+    message: 'cannot writeBatch, given 2 type names but 1 values',
+  });
+  t.throws(() => buf.writeBatch(['int', 'long'], [1, 2]), { code: 'InvalidArg' });
+  t.is(buf.getWriterIndex(), buf.getReaderIndex());
+})
+
+test('test peek window', (t) => {
+  const buf = new ByteBuf(Buffer.from([0xff, 0x01, 0x02, 0x03, 0x04]));
+  buf.readByte();
+  const window = buf.peekWindow(3);
+  t.deepEqual(window, Buffer.from([0x01, 0x02, 0x03]));
+  t.is(buf.getReaderIndex(), 1);
+  t.is(buf.readShort(), 0x0102);
+  t.deepEqual(buf.peekWindow(2), Buffer.from([0x03, 0x04]));
+  t.is(buf.peekWindow(0).length, 0);
+  t.throws(() => buf.peekWindow(3), { code: 'UNDERFLOW' });
+  const header = buf.peekWindow(2);
+  buf.writeIntArray(new Int32Array(1024).fill(-1));
+  t.deepEqual(header, Buffer.from([0x03, 0x04]));
+})
+
+test('test var int max bits', (t) => {
+  const buf = new ByteBuf(Buffer.from([0xff, 0xff, 0x7f, 0xff, 0xff, 0xff, 0x01]));
+  t.is(buf.readVarInt(21), 0x1fffff);
+  t.throws(() => buf.readVarInt(21), {
+    code: 'OVERFLOW',
+    message: 'cannot readVarInt, VarInt is longer than 3 bytes',
+  });
+  t.is(buf.getReaderIndex(), 3);
+  t.is(buf.readVarInt(), 0x3fffff);
+  t.throws(() => new ByteBuf(Buffer.from([0x80, 0x02])).readVarInt(8), {
+    code: 'OVERFLOW',
+    message: 'cannot readVarInt, VarInt is wider than 8 bits',
+  });
+  t.throws(() => buf.readVarInt(33), { code: 'InvalidArg' });
+})
+
+test('test last index of', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x0a, 0x01, 0x0a, 0x02, 0x0a]));
+  buf.readByte();
+  t.is(buf.lastIndexOf(0x0a), 4);
+  t.is(buf.indexOf(0x0a), 2);
+  t.is(buf.lastIndexOf(0x01), 1);
+  t.is(buf.lastIndexOf(0x03), -1);
+  buf.skipBytes(4);
+  t.is(buf.lastIndexOf(0x0a), -1);
+})
+
+test('test bcd', (t) => {
+  const buf = new ByteBuf();
+  buf.writeBcd('12345');
+  buf.writeBcd('0987');
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x12, 0x34, 0x5f, 0x09, 0x87]));
+  t.is(buf.readBcd(3), '12345');
+  t.is(buf.readBcd(2), '0987');
+  t.throws(() => buf.writeBcd('12A'), {
+    code: 'InvalidArg',
+    message: "cannot writeBcd, given digits contain non-digit 'A'",
+  });
+  t.is(buf.getWriterIndex(), 5);
+  const bad = new ByteBuf(Buffer.from([0x1f, 0x23]));
+  t.throws(() => bad.readBcd(2), {
+    code: 'GenericFailure',
+    message: 'cannot readBcd, nibble 0xf at index 0 is not a digit',
+  });
+  t.is(bad.getReaderIndex(), 0);
+})
+
+test('test release', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04]));
+  buf.readByte();
+  buf.release();
+  buf.release();
+  t.throws(() => buf.readInt(), {
+    code: 'GenericFailure',
+    message: 'cannot readInt, buffer released',
+  });
+  t.throws(() => buf.writeByte(1), { message: 'cannot writeByte, buffer released' });
+  t.throws(() => buf.setReaderIndex(0), { message: 'cannot setReaderIndex, buffer released' });
+  t.throws(() => buf.getFloat(0), { message: 'cannot getFloat, buffer released' });
+})
+
+test('test every accessor errors after release', (t) => {
+  // Configuration accessors don't touch the bytes and keep working
+  const config = [
+    'setZeroOnClear', 'setCapacityNormalization', 'setMaxCapacity', 'getMaxCapacity', 'isFull',
+    'setDefaultOrder', 'lockOrder', 'getDefaultOrder', 'isReadOnly', 'setDebugPreviewLength',
+    'onGrow', 'onReadable', 'setBitOrder', 'setAutoGrow',
+  ];
+  const field = { name: 'a', type: 'byte' };
+  const args = {
+    clear: [], getCapacity: [], setCapacity: [8], readableWindow: [], split: [0], readers: [1],
+    duplicateWithIndices: [0, 0], getArray: [], getBuffer: [], peekAll: [], toFullBuffer: [],
+    toArrayBuffer: [], copyToArrayBuffer: [new ArrayBuffer(1), 0], getReadableBytes: [],
+    readableBytesUsize: [], toUtf8String: [], indices: [], hashCode: [], inspect: [],
+    skipBytes: [0], skipUpTo: [0], alignReader: [1], readBoolean: [], readByte: [],
+    readUnsignedByte: [], readShort: [], readShortLE: [], readUnsignedShort: [],
+    readUnsignedShortLE: [], readMedium: [], readInt24Signed: [], readInt24SignedLE: [],
+    readInt24Unsigned: [], readInt24UnsignedLE: [], readInt: [], readUnsignedInt: [],
+    readUnsignedIntBigInt: [], readLongLE: [], readUnsignedLong: [], readUnsignedLongLE: [],
+    readFloat: [], readDouble: [], getFloat: [0], getDouble: [0], validateTotalLength: [0],
+    readHalf: [], readLong: [], readByteOr: [0], readShortOr: [0], readIntOr: [0],
+    readLongOr: [0n], readSizedByteArray: [1], writeSizedByteArray: [1, Buffer.alloc(0)],
+    readVarInt: [], readVarIntSize: [], peekVarInt: [], readVarIntDelta: [], readVarMedium: [],
+    readTlv: [], readVarLong: [], readString: [], readStringLimited: [0, 0], readDate: [],
+    readIntArray: [0], readFloatArray: [0], readDoubleArray: [0], readEnum: [1],
+    readVarIntPrefixedBuf: [], decodeFrames: [() => {}], debugAssertAscii: [0],
+    readUtf16String: [false], readVarLongArray: [0], readStringList: [0], readRemaining: [],
+    readScalar: ['byte'], readIntoUint8Array: [0], readHex: [0], readBcd: [0], readBits: [0],
+    readBitsLE: [0], readBitsWide: [0], peekAt: [0], indexOf: [0], lastIndexOf: [0],
+    countWhile: [() => true], skipWhile: [() => true], readBytesCopy: [0],
+    readBytesReversed: [0], readStringToEnd: ['utf8'], readBatch: [[]], readStruct: [[field]],
+    readStructList: ['byte', [field], 0], peekWindow: [0], append: [Buffer.alloc(0)],
+    appendFrom: [new ByteBuf(), 0], writeBoolean: [true], writeByte: [0], writeShort: [0],
+    writeMedium: [0], writeUnsignedMedium: [0], writeHalf: [0], writeInt: [0], writeLong: [0n],
+    writeLongLE: [0n], writeUnsignedLong: [0n], writeUnsignedLongLE: [0n], writeFloat: [0],
+    writeDouble: [0], writeVarInt: [0], writeVarMedium: [0], writeVarLong: [0n],
+    writeVarIntDelta: [0], writeString: [''], writeStringCapped: ['', 0], writeBase64: [''],
+    writeVarIntBytes: [Buffer.alloc(0)], writeTlv: [0, Buffer.alloc(0)], writeBcd: [''],
+    writeDate: [new Date(0)], writeIntArray: [new Int32Array(0)],
+    writeFloatArray: [new Float32Array(0)], writeDoubleArray: [new Float64Array(0)],
+    writableSlice: [0], commitWritableSlice: [new Uint8Array(0)], writeEnum: [0, 1],
+    writeVarIntMax: [0, 1], writeUtf16String: ['', false], writeVarLongArray: [[]],
+    writeStringList: [[]], writeTransaction: [() => {}], writeLengthPrefixedStruct: [() => {}],
+    writeScalar: ['byte', 0], swapShortEndianness: [0, 0], swapIntEndianness: [0, 0],
+    swapLongEndianness: [0, 0], writeBits: [0, 1], writeBitsWide: [0n, 1], writeBitsLE: [0, 1],
+    alignWriter: [1], fill: [0, () => 0], setInt: [0, 0], writeBatch: [[], []],
+    writeStruct: [[field], { a: 0 }], setReaderIndex: [0], cursor: [], setReaderIndexStrict: [0],
+    getReaderIndex: [], compact: [], discardSomeReadBytes: [], setWriterIndex: [0],
+    setWriterIndexStrict: [0], getWriterIndex: [], setIndex: [0, 0], normalizeIndices: [],
+  };
+  const methods = Object.getOwnPropertyNames(ByteBuf.prototype)
+    .filter((name) => !['constructor', 'release', ...config].includes(name));
+  t.deepEqual(methods.filter((name) => !(name in args)), []);
+
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04]));
+  buf.release();
+  for (const name of methods) {
+    t.throws(() => buf[name](...args[name]), { message: `cannot ${name}, buffer released` });
+  }
+  buf.setAutoGrow(true);
+  t.false(buf.isFull());
+})
+
+test('test capacity normalization', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03]));
+  buf.setCapacityNormalization(true);
+  const capacities = new Set();
+  for (let i = 0; i < 60; i++) {
+    buf.writeByte(i);
+    capacities.add(buf.getCapacity());
+  }
+  t.deepEqual([...capacities], [4, 8, 16, 32, 64]);
+  t.is(buf.getWriterIndex(), 63);
+  buf.setAutoGrow(true);
+  buf.setWriterIndex(100);
+  t.is(buf.getCapacity(), 128);
+})
+
+test('test peek var int', (t) => {
+  const buf = new ByteBuf(Buffer.from([0xac, 0x02, 0x80]));
+  t.is(buf.peekVarInt(), 300);
+  t.is(buf.getReaderIndex(), 0);
+  t.is(buf.readVarInt(), 300);
+  t.is(buf.peekVarInt(), null);
+  t.is(buf.getReaderIndex(), 2);
+  buf.writeByte(0x01);
+  t.is(buf.peekVarInt(), 128);
+})
+
+test('test write base64', (t) => {
+  const buf = new ByteBuf();
+  t.is(buf.writeBase64('aGVsbG8='), 5);
+  t.is(buf.writeBase64('+/8'), 2);
+  t.is(buf.writeBase64('-_8', true), 2);
+  t.is(buf.writeBase64(''), 0);
+  t.deepEqual(buf.getBuffer(), Buffer.from([...Buffer.from('hello'), 0xfb, 0xff, 0xfb, 0xff]));
+  t.throws(() => buf.writeBase64('-_8'), {
+    code: 'InvalidArg',
+    message: "cannot writeBase64, given string has invalid character '-' at 0",
+  });
+  t.throws(() => buf.writeBase64('aGVsb'), {
+    code: 'InvalidArg',
+    message: 'cannot writeBase64, given string length 5 is not valid base64',
+  });
+  t.throws(() => buf.writeBase64('aGU=='), { code: 'InvalidArg' });
+  t.is(buf.getWriterIndex(), 9);
+})
+
+test('test read struct', (t) => {
+  const buf = new ByteBuf();
+  buf.writeInt(42);
+  buf.writeString('steve');
+  buf.writeByte(1);
+  const fields = [
+    { name: 'id', type: 'int' },
+    { name: 'name', type: 'string' },
+  ];
+  t.deepEqual(buf.readStruct(fields), { id: 42, name: 'steve' });
+  t.is(buf.getReaderIndex(), 10);
+  t.throws(() => buf.readStruct([{ name: 'flag', type: 'bool' }, { name: 'id', type: 'int' }]), {
+    code: 'UNDERFLOW',
+  });
+  t.is(buf.getReaderIndex(), 10);
+  t.throws(() => buf.readStruct([{ name: 'x', type: 'uuid' }]), { code: 'InvalidArg' });
+})
+
+test('test write struct', (t) => {
+  const fields = [
+    { name: 'id', type: 'int' },
+    { name: 'name', type: 'string' },
+    { name: 'uid', type: 'long' },
+  ];
+  const buf = new ByteBuf();
+  buf.writeStruct(fields, { id: 7, name: 'alex', uid: 1n << 40n });
+  t.is(buf.getWriterIndex(), 17);
+  t.deepEqual(buf.readStruct(fields), { id: 7, name: 'alex', uid: 1n << 40n });
+  t.throws(() => buf.writeStruct(fields, { id: 7, name: 'alex' }), {
+    code: 'InvalidArg',
+    message: 'cannot writeStruct, given value is missing field uid',
+  });
+  t.throws(() => buf.writeStruct(fields, { id: 7, name: 1, uid: 0n }), {
+    code: 'InvalidArg',
+    message: 'cannot writeStruct, expected String for name but got Number',
+  });
+  t.throws(() => buf.writeStruct(fields, { id: '7', name: 'alex', uid: 0n }), { code: 'InvalidArg' });
+  t.is(buf.getWriterIndex(), 17);
+})
+
+test('test medium range', (t) => {
+  const buf = new ByteBuf();
+  buf.writeMedium(-8388608);
+  buf.writeMedium(8388607);
+  buf.writeUnsignedMedium(0);
+  buf.writeUnsignedMedium(16777215);
+  t.throws(() => buf.writeMedium(8388608), {
+    code: 'InvalidArg',
+    message: 'cannot writeMedium, given value 8388608 is not between -8388608 and 8388607',
+  });
+  t.throws(() => buf.writeMedium(-8388609), { code: 'InvalidArg' });
+  t.throws(() => buf.writeUnsignedMedium(16777216), {
+    code: 'InvalidArg',
+    message: 'cannot writeUnsignedMedium, given value 16777216 is not between 0 and 16777215',
+  });
+  t.is(buf.getWriterIndex(), 12);
+  t.is(buf.readMedium(), -8388608);
+  t.is(buf.readMedium(), 8388607);
+  t.is(buf.readInt24Unsigned(), 0);
+  t.is(buf.readInt24Unsigned(), 16777215);
+})
+
+test('test readable bytes usize', (t) => {
+  const buf = new ByteBuf(Buffer.alloc(300));
+  buf.skipBytes(44);
+  t.is(buf.readableBytesUsize(), 256n);
+  t.is(buf.readableBytesUsize(), BigInt(buf.getReadableBytes()));
+  buf.skipBytes(256);
+  t.is(buf.readableBytesUsize(), 0n);
+})
+
+test('test append from', (t) => {
+  const source = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04, 0x05]));
+  source.readByte();
+  const target = new ByteBuf(Buffer.from([0xff]));
+  target.appendFrom(source, 3);
+  t.is(source.getReaderIndex(), 4);
+  t.is(target.getWriterIndex(), 4);
+  t.deepEqual(target.getBuffer(), Buffer.from([0xff, 0x02, 0x03, 0x04]));
+  t.throws(() => target.appendFrom(source, 2), {
+    code: 'UNDERFLOW',
+    message: 'cannot appendFrom, readableBytes 1 is less than 2 at readerIndex 4',
+  });
+  t.throws(() => target.appendFrom(target, 1), { code: 'InvalidArg' });
+  t.is(target.getWriterIndex(), 4);
+})
+
+test('test peek all', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x00, 0x00, 0x01, 0x02, 0x03]));
+  t.deepEqual(buf.peekAll(), Buffer.from([0x00, 0x00, 0x01, 0x02, 0x03]));
+  t.is(buf.readInt(), 0x0102);
+  t.deepEqual(buf.peekAll(), Buffer.from([0x03]));
+})
+
+test('test var int delta', (t) => {
+  const values = [1000, 1003, 1010, 1200, 1201, 990];
+  const buf = new ByteBuf();
+  const sizes = values.map((v) => buf.writeVarIntDelta(v));
+  t.deepEqual(sizes, [2, 1, 1, 2, 1, 2]);
+  t.deepEqual(values.map(() => buf.readVarIntDelta()), values);
+  buf.clear();
+  buf.writeVarIntDelta(-1);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01]));
+  t.is(buf.readVarIntDelta(), -1);
+
+  const reread = new ByteBuf();
+  reread.writeVarIntDelta(100);
+  t.is(reread.readVarIntDelta(), 100);
+  reread.setReaderIndex(0);
+  t.is(reread.readVarIntDelta(), 100);
+
+  const rollback = new ByteBuf();
+  t.throws(() =>
+    rollback.writeTransaction(() => {
+      rollback.writeVarIntDelta(100);
+      throw new Error('serialization failed');
+    })
+  );
+  rollback.writeVarIntDelta(105);
+  t.is(rollback.readVarIntDelta(), 105);
+
+  const rejected = ByteBuf.withInitialCapacity(2);
+  rejected.onGrow(() => {
+    throw new Error('grow rejected');
+  });
+  rejected.writeVarIntDelta(100);
+  t.throws(() => rejected.writeVarIntDelta(300), { message: 'grow rejected' });
+  rejected.onGrow(null);
+  rejected.writeVarIntDelta(301);
+  t.deepEqual([rejected.readVarIntDelta(), rejected.readVarIntDelta()], [100, 301]);
+  t.is(rejected.getReadableBytes(), 0);
+})
+
+test('test debug preview length', (t) => {
+  const buf = new ByteBuf(Buffer.alloc(20, 0xab));
+  t.true(buf.inspect().endsWith(`[${Array(16).fill('ab').join(' ')} ...]`));
+  buf.setDebugPreviewLength(2);
+  t.is(buf.inspect(), 'ByteBuf(readerIndex=0, writerIndex=20, capacity=20, readable=20) [ab ab ...]');
+  buf.setDebugPreviewLength(20);
+  t.true(buf.inspect().endsWith(`[${Array(20).fill('ab').join(' ')}]`));
+  buf.setDebugPreviewLength(0);
+  t.true(buf.inspect().endsWith('[ ...]'));
+})
+
+test('test read unsigned int bigint', (t) => {
+  const buf = new ByteBuf(Buffer.from([0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xff, 0xfe, 0x01]));
+  t.is(buf.readUnsignedInt(), 4294967294);
+  t.is(buf.readUnsignedIntBigInt(), 4294967294n);
+  t.throws(() => buf.readUnsignedIntBigInt(), {
+    code: 'UNDERFLOW',
+    message: 'cannot readUnsignedIntBigInt, readableBytes 1 is less than 4 at readerIndex 8',
+  });
+})
+
+test('test bit order', (t) => {
+  const msb = new ByteBuf();
+  msb.writeBits(0xabc, 12);
+  t.deepEqual(msb.getBuffer(), Buffer.from([0xab, 0xc0]));
+  t.is(msb.readBits(12), 0xabc);
+
+  const lsb = new ByteBuf();
+  lsb.setBitOrder(false);
+  lsb.writeBits(0xabc, 12);
+  t.deepEqual(lsb.getBuffer(), Buffer.from([0xbc, 0x0a]));
+  t.is(lsb.readBits(12), 0xabc);
+  t.throws(() => lsb.readBits(5), {
+    code: 'UNDERFLOW',
+    message: 'cannot readBits, readable bits 4 are less than 5 at readerIndex 2',
+  });
+  lsb.setBitOrder(true);
+  lsb.setReaderIndex(0);
+  t.is(lsb.readBits(12), 0xbc0);
+
+  const mixed = new ByteBuf(Buffer.from([0b1010_0101, 0b1100_0011, 0b0110_1001, 0b1111_0110]));
+  mixed.setBitOrder(false);
+  t.is(mixed.readBits(3), 0b101);
+  t.is(mixed.readBitsLE(4), 0b0011);
+  t.is(mixed.getReaderIndex(), 2);
+  t.is(mixed.readBits(2), 0b01);
+  t.is(mixed.readBitsLE(4), 0b0110);
+  t.is(mixed.getReaderIndex(), 4);
+
+  const packed = new ByteBuf();
+  packed.setBitOrder(false);
+  packed.writeBits(0b101, 3);
+  packed.writeBitsLE(0b11, 2);
+  packed.writeBits(0b1, 1);
+  t.deepEqual(packed.getBuffer(), Buffer.from([0b0000_0101, 0b0000_0011, 0b0000_0001]));
+  packed.writeBitsLE(0b1, 1);
+  packed.setReaderIndex(1);
+  packed.writeBitsLE(0b1, 1);
+  t.deepEqual(packed.getBuffer(), Buffer.from([0b0000_0011, 0b0000_0001, 0b0000_0011]));
+  t.is(packed.getWriterIndex(), 4);
+})
+
+test('test from array buffer', (t) => {
+  const ab = new ArrayBuffer(6);
+  new DataView(ab).setInt32(0, 0x01020304);
+  new DataView(ab).setInt16(4, -2);
+  const buf = ByteBuf.fromArrayBuffer(ab);
+  t.is(buf.getWriterIndex(), 6);
+  t.is(buf.readInt(), 0x01020304);
+  t.is(buf.readShort(), -2);
+  new DataView(ab).setInt8(0, 0);
+  buf.setReaderIndex(0);
+  t.is(buf.readByte(), 1);
+})
+
+test('test var medium', (t) => {
+  const buf = new ByteBuf();
+  t.is(buf.writeVarMedium(100), 1);
+  t.is(buf.writeVarMedium(2097151), 3);
+  t.is(buf.writeVarMedium(16777215), 4);
+  t.throws(() => buf.writeVarMedium(16777216), { code: 'InvalidArg' });
+  t.is(buf.getWriterIndex(), 8);
+  t.is(buf.readVarMedium(), 100);
+  t.is(buf.readVarMedium(), 2097151);
+  t.is(buf.readVarMedium(), 16777215);
+  buf.writeVarInt(16777216);
+  t.throws(() => buf.readVarMedium(), {
+    code: 'OVERFLOW',
+    message: 'cannot readVarMedium, VarInt is wider than 24 bits',
+  });
+})
+
+test('test validate total length', (t) => {
+  const buf = new ByteBuf();
+  buf.writeByte(0x01);
+  buf.writeInt(9);
+  buf.writeInt(0xcafe);
+  t.true(buf.validateTotalLength(1));
+  buf.writeByte(0x00);
+  t.false(buf.validateTotalLength(1));
+  t.false(buf.validateTotalLength(0));
+  t.throws(() => buf.validateTotalLength(7), {
+    code: 'InvalidArg',
+    message: 'cannot validateTotalLength, given range 7..11 exceeds writerIndex 10',
+  });
+})
+
+test('test append', (t) => {
+  const bytes = [0x01, 0x02, 0x03];
+  const fromBuffer = new ByteBuf();
+  fromBuffer.append(Buffer.from(bytes));
+  const fromArray = new ByteBuf();
+  fromArray.append(new Uint8Array(bytes));
+  fromArray.append(new Uint8Array(new ArrayBuffer(8), 2, 0));
+  t.is(fromBuffer.getWriterIndex(), 3);
+  t.is(fromArray.getWriterIndex(), 3);
+  t.deepEqual(fromArray.getBuffer(), fromBuffer.getBuffer());
+  t.deepEqual(fromBuffer.getBuffer(), Buffer.from(bytes));
+})
+
+test('test wide bits', (t) => {
+  const value = 0xab_cdef_0123n;
+  const buf = new ByteBuf();
+  buf.writeBits(0b101, 3);
+  buf.writeBitsWide(value, 40);
+  buf.writeBitsWide(1n, 5);
+  t.is(buf.getWriterIndex(), 6);
+  t.is(buf.readBits(3), 0b101);
+  t.is(buf.readBitsWide(40), value);
+  t.is(buf.readBitsWide(5), 1n);
+
+  const lsb = new ByteBuf();
+  lsb.setBitOrder(false);
+  lsb.writeBitsWide(0xffff_ffff_ffff_ffffn, 64);
+  lsb.writeBitsWide(value, 40);
+  t.is(lsb.readBitsWide(64), 0xffff_ffff_ffff_ffffn);
+  t.is(lsb.readBitsWide(40), value);
+  t.throws(() => buf.writeBitsWide(value, 39), { code: 'InvalidArg' });
+  t.throws(() => buf.readBitsWide(65), {
+    code: 'InvalidArg',
+    message: 'cannot readBitsWide, given count 65 is not within 1..=64',
+  });
+})
+
+test('test read or default', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x00, 0x00, 0x00, 0x2a, 0xff, 0xfe, 0x07]));
+  t.is(buf.readIntOr(-1), 42);
+  t.is(buf.readLongOr(-1n), -1n);
+  t.is(buf.getReaderIndex(), 4);
+  t.is(buf.readShortOr(0), -2);
+  t.is(buf.readIntOr(-1), -1);
+  t.is(buf.readShortOr(9), 9);
+  t.is(buf.readByteOr(0), 7);
+  t.is(buf.readByteOr(-5), -5);
+  t.is(buf.getReaderIndex(), 7);
+})
+
+test('test normalize indices', (t) => {
+  const buf = ByteBuf.withInitialCapacity(16);
+  buf.writeInt(1);
+  t.false(buf.normalizeIndices());
+  buf.setIndex(2, 3);
+  t.false(buf.normalizeIndices());
+  t.is(buf.getReaderIndex(), 2);
+  t.is(buf.getWriterIndex(), 3);
+  buf.setIndex(0, 4);
+  t.is(buf.readInt(), 1);
+})
+
+test('test write length prefixed struct', (t) => {
+  const buf = new ByteBuf();
+  buf.writeByte(0x7f);
+  const body = Buffer.alloc(200, 0xaa);
+  t.is(buf.writeLengthPrefixedStruct(() => buf.append(body)), 200);
+  t.is(buf.getWriterIndex(), 1 + 2 + 200);
+  t.is(buf.writeLengthPrefixedStruct(() => buf.writeShort(5)), 2);
+  t.throws(
+    () =>
+      buf.writeLengthPrefixedStruct(() => {
+        buf.writeInt(1);
+        throw new Error('boom');
+      }),
+    { message: 'boom' },
+  );
+  t.throws(
+    () =>
+      buf.writeLengthPrefixedStruct(() => {
+        buf.writeInt(1);
+        buf.compact();
+      }),
+    { message: 'cannot compact, buffer is in use by writeLengthPrefixedStruct' },
+  );
+  t.throws(
+    () =>
+      buf.writeLengthPrefixedStruct(() => {
+        buf.writeInt(1);
+        buf.setInt(206, -1);
+      }),
+    { message: 'cannot writeLengthPrefixedStruct, callback overwrote the length prefix' },
+  );
+  t.is(buf.getWriterIndex(), 206);
+  t.is(buf.readByte(), 0x7f);
+  t.is(buf.readVarInt(), 200);
+  t.deepEqual(Buffer.from(buf.readIntoUint8Array(200)), body);
+  t.is(buf.readVarInt(), 2);
+  t.is(buf.readShort(), 5);
+})
+
+test('test read struct list', (t) => {
+  const fields = [
+    { name: 'id', type: 'varint' },
+    { name: 'name', type: 'string' },
+    { name: 'online', type: 'bool' },
+  ];
+  const buf = new ByteBuf();
+  buf.writeShort(0);
+  buf.writeShort(2);
+  buf.writeStruct(fields, { id: 1, name: 'a', online: true });
+  buf.writeStruct(fields, { id: 300, name: 'longer name', online: false });
+  buf.writeVarInt(3);
+  t.deepEqual(buf.readStructList('short', fields, 8), []);
+  t.deepEqual(buf.readStructList('short', fields, 8), [
+    { id: 1, name: 'a', online: true },
+    { id: 300, name: 'longer name', online: false },
+  ]);
+  t.throws(() => buf.readStructList('varint', fields, 2), {
+    code: 'OVERFLOW',
+    message: 'cannot readStructList, count 3 is greater than maxCount 2',
+  });
+  t.throws(() => buf.readStructList('varint', fields, 3), { code: 'UNDERFLOW' });
+  t.throws(() => buf.readStructList('uuid', fields, 3), { code: 'InvalidArg' });
+  t.is(buf.readVarInt(), 3);
+})
+
+test('test lock order', (t) => {
+  const buf = new ByteBuf();
+  buf.lockOrder(ByteOrder.LittleEndian);
+  t.throws(() => buf.setDefaultOrder(ByteOrder.BigEndian), {
+    code: 'GenericFailure',
+    message: 'cannot setDefaultOrder, byte order is locked',
+  });
+  buf.lockOrder(ByteOrder.LittleEndian);
+  t.throws(() => buf.lockOrder(ByteOrder.BigEndian), { code: 'GenericFailure' });
+  t.is(buf.getDefaultOrder(), ByteOrder.LittleEndian);
+  buf.writeFloat(1.5);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x00, 0x00, 0xc0, 0x3f]));
+  t.is(buf.readFloat(), 1.5);
+})
+
+test('test var int size', (t) => {
+  t.is(ByteBuf.varIntSize(0), 1);
+  t.is(ByteBuf.varIntSize(127), 1);
+  t.is(ByteBuf.varIntSize(128), 2);
+  t.is(ByteBuf.varIntSize(-1), 5);
+  t.is(ByteBuf.varLongSize(0n), 1);
+  t.is(ByteBuf.varLongSize(128n), 2);
+  t.is(ByteBuf.varLongSize(-1n), 10);
+  const buf = new ByteBuf();
+  for (const val of [0, 300, 2097152, -5]) {
+    t.is(buf.writeVarInt(val), ByteBuf.varIntSize(val));
+  }
+  t.is(buf.writeVarLong(1n << 49n), ByteBuf.varLongSize(1n << 49n));
+})
+
+test('test count while', (t) => {
+  const buf = new ByteBuf(Buffer.from('x   \tkey'));
+  const isSpace = (b) => b === 0x20 || b === 0x09;
+  t.is(buf.countWhile(isSpace), 0);
+  buf.readByte();
+  t.is(buf.countWhile(isSpace), 4);
+  t.is(buf.getReaderIndex(), 1);
+  t.is(buf.countWhile(() => 1), 7);
+  t.throws(
+    () =>
+      buf.countWhile(() => {
+        throw new TypeError('bad predicate');
+      }),
+    { message: 'bad predicate' },
+  );
+  t.throws(() => buf.countWhile(() => buf.readByte()), {
+    message: 'cannot readByte, buffer is in use by countWhile',
+  });
+  t.is(buf.getReaderIndex(), 1);
+})
+
+test('test skip while', (t) => {
+  const buf = new ByteBuf(Buffer.from('  \t value'));
+  const isSpace = (b) => b === 0x20 || b === 0x09;
+  t.is(buf.skipWhile(isSpace), 4);
+  t.is(buf.getReaderIndex(), 4);
+  t.is(buf.skipWhile(isSpace), 0);
+  t.is(buf.getReaderIndex(), 4);
+  t.is(buf.toUtf8String(), 'value');
+})
+
+test('test write var int bytes', (t) => {
+  const buf = new ByteBuf();
+  const data = Buffer.alloc(130, 0x5a);
+  buf.writeVarIntBytes(data);
+  buf.writeVarIntBytes(Buffer.from([0x01]));
+  t.is(buf.getWriterIndex(), 2 + 130 + 1 + 1);
+  t.is(buf.readVarInt(), 130);
+  t.deepEqual(Buffer.from(buf.readIntoUint8Array(130)), data);
+  t.deepEqual(buf.getBuffer(), Buffer.from([0x01, 0x01]));
+  buf.setMaxCapacity(buf.getWriterIndex() + 2);
+  t.throws(() => buf.writeVarIntBytes(Buffer.from([0x01, 0x02])), { code: 'OVERFLOW' });
+  t.is(buf.getWriterIndex(), 134);
+
+  const rejected = ByteBuf.withInitialCapacity(2);
+  rejected.onGrow(() => {
+    throw new Error('grow rejected');
+  });
+  t.throws(() => rejected.writeVarIntBytes(Buffer.from([0x01, 0x02, 0x03])), {
+    message: 'grow rejected',
+  });
+  t.is(rejected.getWriterIndex(), 0);
+  rejected.onGrow(null);
+  rejected.writeVarIntBytes(Buffer.from([0x04]));
+  t.deepEqual(rejected.getBuffer(), Buffer.from([0x01, 0x04]));
+})
+
+test('test on readable', (t) => {
+  const buf = new ByteBuf();
+  const events = [];
+  buf.onReadable(6, (readable) => events.push(readable));
+  buf.writeShort(1);
+  buf.writeShort(2);
+  t.deepEqual(events, []);
+  buf.writeInt(3);
+  t.deepEqual(events, [8]);
+  buf.writeInt(4);
+  t.deepEqual(events, [8]);
+  buf.skipBytes(10);
+  buf.writableSlice(6);
+  t.deepEqual(events, [8, 8]);
+  buf.setAutoGrow(true);
+  buf.setIndex(22, 22);
+  buf.setWriterIndex(30);
+  t.deepEqual(events, [8, 8, 8]);
+  buf.onReadable(1, () => buf.readByte());
+  buf.skipBytes(8);
+  t.throws(() => buf.writeByte(5), {
+    message: 'cannot readByte, buffer is in use by onReadable',
+  });
+  t.is(buf.getWriterIndex(), 30);
+  buf.onReadable(0, null);
+  buf.writeLong(0n);
+  t.deepEqual(events, [8, 8, 8]);
+
+  const rejected = new ByteBuf();
+  rejected.onReadable(4, () => {
+    throw new Error('readable rejected');
+  });
+  rejected.writeShort(1);
+  t.throws(() => rejected.writeShort(2), { message: 'readable rejected' });
+  t.is(rejected.getWriterIndex(), 2);
+  rejected.setAutoGrow(true);
+  t.throws(() => rejected.setWriterIndex(8), { message: 'readable rejected' });
+  t.throws(() => rejected.setIndex(1, 8), { message: 'readable rejected' });
+  t.is(rejected.getReaderIndex(), 0);
+  t.is(rejected.getWriterIndex(), 2);
+  rejected.onReadable(0, null);
+  rejected.setWriterIndex(4);
+  t.deepEqual(rejected.getBuffer(), Buffer.from([0x00, 0x01, 0x00, 0x00]));
+})
+
+test('test from byte array capacity', (t) => {
+  const buf = ByteBuf.fromByteArray([0x01, 0x02, 0x03]);
+  t.is(buf.getCapacity(), 3);
+  t.is(buf.getWriterIndex(), 3);
+  t.throws(() => buf.setWriterIndex(4), {
+    code: 'InvalidArg',
+    message: 'cannot setWriterIndex, given writerIndex 4 is greater than capacity 3',
+  });
+  t.deepEqual(buf.readRemaining(), Buffer.from([0x01, 0x02, 0x03]));
+
+  buf.clear();
+  buf.setWriterIndex(3);
+  t.deepEqual(buf.getBuffer(), Buffer.alloc(3));
+})
+
+test('test read bytes copy', (t) => {
+  const buf = ByteBuf.fromByteArray([0x01, 0x02, 0x03, 0x04]);
+  const copy = buf.readBytesCopy(3);
+  t.deepEqual(copy, Buffer.from([0x01, 0x02, 0x03]));
+  t.is(buf.getReaderIndex(), 3);
+  // Forces the backing store to move
+  buf.writeIntArray(new Int32Array(1024).fill(-1));
+  t.true(buf.getCapacity() > 4);
+  t.deepEqual(copy, Buffer.from([0x01, 0x02, 0x03]));
+  t.throws(() => buf.readBytesCopy(5000), { code: 'UNDERFLOW' });
+})
+
+test('test readers', (t) => {
+  const buf = new ByteBuf(Buffer.from([0xff, 0x00, 0x01, 0x00, 0x02]));
+  buf.readByte();
+  const [a, b, c] = buf.readers(3);
+  t.is(buf.readers(0).length, 0);
+  t.is(a.readShort(), 1);
+  t.is(b.readInt(), 0x00010002);
+  t.is(a.readShort(), 2);
+  t.is(c.getReaderIndex(), 0);
+  t.is(c.getReadableBytes(), 4);
+  t.is(buf.getReaderIndex(), 1);
+})
+
+test('test tlv', (t) => {
+  const buf = new ByteBuf();
+  buf.writeTlv(1, Buffer.from('abc'));
+  buf.writeTlv(300, Buffer.alloc(0));
+  buf.writeTlv(7, Buffer.from([0xff, 0xfe]));
+  t.deepEqual(buf.getBuffer().subarray(0, 5), Buffer.from([0x01, 0x03, 0x61, 0x62, 0x63]));
+  const records = [];
+  while (buf.getReadableBytes() > 0) {
+    records.push(buf.readTlv());
+  }
+  t.deepEqual(records, [
+    { tag: 1, value: Buffer.from('abc') },
+    { tag: 300, value: Buffer.alloc(0) },
+    { tag: 7, value: Buffer.from([0xff, 0xfe]) },
+  ]);
+  buf.writeTlv(2, Buffer.from([0x01, 0x02, 0x03]));
+  buf.setWriterIndex(buf.getWriterIndex() - 1);
+  t.throws(() => buf.readTlv(), {
+    code: 'UNDERFLOW',
+    message: 'cannot readTlv, readableBytes 4 is less than 5 at readerIndex 12',
+  });
+  t.is(buf.getReaderIndex(), 12);
+
+  const rejected = ByteBuf.withInitialCapacity(2);
+  rejected.onGrow(() => {
+    throw new Error('grow rejected');
+  });
+  t.throws(() => rejected.writeTlv(1, Buffer.from('abc')), { message: 'grow rejected' });
+  t.is(rejected.getWriterIndex(), 0);
+  rejected.onGrow(null);
+  rejected.writeTlv(1, Buffer.alloc(0));
+  t.deepEqual(rejected.readTlv(), { tag: 1, value: Buffer.alloc(0) });
+})
+
+test('test read string limited', (t) => {
+  const buf = new ByteBuf();
+  buf.writeString('héllo');
+  buf.writeString('abcdef');
+  t.throws(() => buf.readStringLimited(5, 10), {
+    code: 'OVERFLOW',
+    message: 'cannot readStringLimited, string length 6 is greater than maxBytes 5',
+  });
+  t.throws(() => buf.readStringLimited(10, 4), {
+    code: 'OVERFLOW',
+    message: 'cannot readStringLimited, string has 5 characters, more than maxChars 4',
+  });
+  t.is(buf.getReaderIndex(), 0);
+  t.is(buf.readStringLimited(6, 5), 'héllo');
+  t.is(buf.readStringLimited(6, 6), 'abcdef');
+})
+
+test('test duplicate with indices', (t) => {
+  const buf = new ByteBuf(Buffer.from([0x01, 0x02, 0x03, 0x04, 0x05, 0x06]));
+  buf.readByte();
+  const dup = buf.duplicateWithIndices(2, 4);
+  t.is(dup.getReaderIndex(), 2);
+  t.is(dup.getWriterIndex(), 4);
+  t.is(dup.readShort(), 0x0304);
+  t.is(dup.getReadableBytes(), 0);
+  dup.setWriterIndex(6);
+  t.is(dup.readShort(), 0x0506);
+  t.is(buf.getReaderIndex(), 1);
+  t.is(buf.getWriterIndex(), 6);
+  t.throws(() => buf.duplicateWithIndices(4, 2), { code: 'InvalidArg' });
+  t.throws(() => buf.duplicateWithIndices(0, 7), {
+    code: 'InvalidArg',
+    message: 'cannot duplicateWithIndices, given writerIndex 7 is greater than written length 6',
+  });
+})
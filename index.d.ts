@@ -3,21 +3,170 @@
 
 /* auto-generated by NAPI-RS */
 
+/** Byte order of the float and double accessors, see `ByteBuf.setDefaultOrder` */
+export const enum ByteOrder {
+  /** Most significant byte first, the default */
+  BigEndian = 0,
+  /** Least significant byte first */
+  LittleEndian = 1
+}
+/** How the byte length of a string is prefixed */
+export const enum StringEncoding {
+  /** VarInt length prefix */
+  VarInt = 0,
+  /** Big-endian u16 length prefix */
+  UnsignedShort = 1,
+  /** Big-endian u32 length prefix */
+  UnsignedInt = 2
+}
+/** All the positional state of a buffer, see `ByteBuf.indices` */
+export interface ByteBufIndices {
+  readerIndex: number
+  writerIndex: number
+  readableBytes: number
+  capacity: number
+}
+/** One named field of a record, see `ByteBuf.readStruct` */
+export interface StructField {
+  name: string
+  /** A `readScalar` type name or `string` */
+  type: string
+}
+/** One tag-length-value record, see `ByteBuf.readTlv` */
+export interface Tlv {
+  tag: number
+  value: Buffer
+}
 export class ByteBuf {
   constructor(buf?: Buffer | undefined | null)
   static withInitialCapacity(initialCapacity: number): ByteBuf
+  /**
+  * Creates a buffer of exactly `size` zero bytes, all of them written,
+  * so absolute setters can patch any offset straight away
+  */
+  static allocate(size: number): ByteBuf
+  /**
+  * Wraps a copy of the given bytes. The capacity is trimmed to the length, so without
+  * auto grow `setWriterIndex` can't move past the given bytes
+  */
   static fromByteArray(byteArray: Array<number>): ByteBuf
+  /** Creates a buffer holding a copy of an ArrayBuffer's bytes, all of them written */
+  static fromArrayBuffer(arrayBuffer: ArrayBuffer): ByteBuf
+  /** Creates a new buffer holding the readable bytes of all the given buffers, in order */
+  static concat(buffers: Array<ByteBuf>): ByteBuf
+  /** How many bytes `writeVarInt` would take for `val`, for precomputing frame sizes */
+  static varIntSize(val: number): number
+  /** How many bytes `writeVarLong` would take for `val` */
+  static varLongSize(val: bigint): number
   clear(): void
+  /**
+  * Frees the backing store right away instead of waiting for GC, any later call touching the
+  * bytes or the indices errors. Calling it again does nothing
+  */
+  release(): void
+  /**
+  * When enabled, `clear` overwrites the whole backing store with zeros first so sensitive
+  * data isn't left behind in memory. Off by default
+  */
+  setZeroOnClear(enabled: boolean): void
   /** Returns the number of bytes this buffer can contain */
   getCapacity(): number
   /** u32 is enough, i64 is too much even for general use */
   setCapacity(size: number): void
+  /**
+  * When enabled, growing rounds the capacity up to the next power of two like Netty does,
+  * so buffers written in small steps reallocate less often. Off by default
+  */
+  setCapacityNormalization(enabled: boolean): void
+  /**
+  * Caps how far writes can move the writer index, writes past it error instead of growing.
+  * Unbounded (null) by default
+  */
+  setMaxCapacity(maxCapacity?: number | undefined | null): void
+  getMaxCapacity(): number | null
+  /** Whether the writer index reached the max capacity, never true when unbounded */
+  isFull(): boolean
+  /**
+  * Sets the byte order of `readFloat`/`readDouble`, `getFloat`/`getDouble`
+  * and `writeFloat`/`writeDouble`, every other accessor has a fixed order.
+  * Errors once `lockOrder` was called
+  */
+  setDefaultOrder(order: ByteOrder): void
+  /**
+  * Sets the default order for good, so a wire format built on this buffer can't be changed
+  * by downstream code: `setDefaultOrder` errors afterwards, and so does locking another order
+  */
+  lockOrder(order: ByteOrder): void
+  getDefaultOrder(): ByteOrder
+  isReadOnly(): boolean
+  /**
+  * Returns a read-only copy of the readable bytes with its own reader index,
+  * so a consumer can parse it while this buffer keeps accumulating writes
+  */
+  readableWindow(): ByteBuf
+  /**
+  * Splits the written bytes into two independent copies, `[0, index)` and
+  * `[index, writerIndex)`, e.g. a header and its body
+  */
+  split(index: number): [ByteBuf, ByteBuf]
+  /**
+  * Returns `n` independent buffers each holding a copy of the readable bytes, with their
+  * reader index at 0, to parse the same payload several ways
+  */
+  readers(n: number): Array<ByteBuf>
+  /**
+  * Returns a copy of the written bytes positioned at the given indices, in one step instead
+  * of copying and then calling `setIndex`. Requires `reader <= writer <= written length`
+  */
+  duplicateWithIndices(reader: number, writer: number): ByteBuf
   /** Involves copying, use with caution */
   getArray(): Uint8Array
-  /** Returns the buffer, zero-copy :) */
+  /** Returns a copy of the readable bytes, safe to keep across writes */
   getBuffer(): Buffer
+  /**
+  * Returns a copy of the whole readable region without advancing, same as `getBuffer`
+  * but named for inspecting next to the advancing reads
+  */
+  peekAll(): Buffer
+  /**
+  * Returns a copy of everything written so far, `[0, writerIndex)`,
+  * including bytes already read
+  */
+  toFullBuffer(): Buffer
+  /**
+  * Returns the readable bytes as an ArrayBuffer, for APIs that don't accept a Buffer.
+  * Zero-copy: the backing store is handed over to the ArrayBuffer, which frees it once
+  * collected, and this buffer is left empty with no capacity, like after `clear` on a fresh
+  * buffer. The two never alias, later writes go to a new backing store
+  */
+  toArrayBuffer(): ArrayBuffer
+  /**
+  * Copies the readable bytes into `target` starting at `offset` without consuming them,
+  * returns the number of bytes copied
+  */
+  copyToArrayBuffer(target: ArrayBuffer, offset: number): number
+  /** Errors rather than truncating if the count doesn't fit in a u32 */
   getReadableBytes(): number
+  /** The exact readable count as a BigInt, for buffers too large for `getReadableBytes` */
+  readableBytesUsize(): bigint
+  /** Decodes all the readable bytes as UTF-8 without advancing the reader index */
+  toUtf8String(): string
+  /** Returns reader / writer index, readable bytes and capacity in a single call */
+  indices(): ByteBufIndices
+  /**
+  * 32-bit FNV-1a hash of the readable bytes, so like Netty's `hashCode` it only depends
+  * on their content and not on the indices or capacity
+  */
+  hashCode(): number
+  /** Sets how many readable bytes `inspect` previews, 16 by default */
+  setDebugPreviewLength(length: number): void
+  /** Compact summary of the indices plus a hex preview of the readable bytes */
+  inspect(): string
   skipBytes(length: number): void
+  /** Skips at most `length` bytes, clamped to readableBytes, and returns how many were skipped */
+  skipUpTo(length: number): number
+  /** Skips padding until the reader index is a multiple of `boundary` */
+  alignReader(boundary: number): void
   readBoolean(): boolean
   readByte(): number
   readUnsignedByte(): number
@@ -25,15 +174,439 @@ export class ByteBuf {
   readShortLE(): number
   readUnsignedShort(): number
   readUnsignedShortLE(): number
+  /** Reads a signed (sign-extended) 24-bit integer */
   readMedium(): number
+  /** Same as `readMedium`, named after its signedness */
+  readInt24Signed(): number
+  readInt24SignedLE(): number
+  readInt24Unsigned(): number
+  readInt24UnsignedLE(): number
+  readInt(): number
+  readUnsignedInt(): number
+  /**
+  * Same as `readUnsignedInt` but as a BigInt, for decoders that handle every wide integer
+  * the way they handle the 64-bit ones
+  */
+  readUnsignedIntBigInt(): bigint
+  readLongLE(): bigint
+  readUnsignedLong(): bigint
+  readUnsignedLongLE(): bigint
+  /** Reads a float in the default order, big-endian unless changed */
+  readFloat(): number
+  /** Reads a double in the default order, big-endian unless changed */
+  readDouble(): number
+  /** Returns the float at the absolute `index` in the default order without moving the indices */
+  getFloat(index: number): number
+  /** Returns the double at the absolute `index` in the default order without moving the indices */
+  getDouble(index: number): number
+  /**
+  * Checks the big-endian int at the absolute `headerIndex` equals the written length, to catch
+  * truncated or corrupt frames that carry their total length up front
+  */
+  validateTotalLength(headerIndex: number): boolean
+  /** Reads a big-endian IEEE-754 half-precision (16-bit) float */
+  readHalf(): number
+  readLong(): bigint
+  /**
+  * Like `readByte` but returns `default` without advancing when there isn't a byte left,
+  * for best-effort parsers
+  */
+  readByteOr(default: number): number
+  /** Like `readShort` but returns `default` without advancing when there aren't 2 bytes left */
+  readShortOr(default: number): number
+  /** Like `readInt` but returns `default` without advancing when there aren't 4 bytes left */
+  readIntOr(default: number): number
+  /** Like `readLong` but returns `default` without advancing when there aren't 8 bytes left */
+  readLongOr(default: bigint): bigint
+  /**
+  * Reads a byte array prefixed by a big-endian length field,
+  * the width of the length field (1, 2 or 4 bytes) is chosen by the caller
+  */
+  readSizedByteArray(lengthBytes: number): Buffer
+  /**
+  * Writes a byte array prefixed by a big-endian length field,
+  * the width of the length field (1, 2 or 4 bytes) is chosen by the caller
+  */
+  writeSizedByteArray(lengthBytes: number, buf: Buffer): void
+  /**
+  * Reads a VarInt, 7 bits per byte, least significant group first (at most 5 bytes)
+  *
+  * `maxBits` (1 to 32, default 32) caps the width for protocols with restricted VarInts, e.g. 21
+  * bits allows at most 3 bytes, longer encodings are rejected
+  */
+  readVarInt(maxBits?: number | undefined | null): number
+  /** Returns how many bytes (1 to 5) the VarInt at the reader index takes, without consuming it */
+  readVarIntSize(): number
+  /**
+  * Decodes the VarInt at the reader index without consuming it, e.g. to sniff a packet id,
+  * or returns null if it hasn't fully arrived yet
+  */
+  peekVarInt(): number | null
+  /**
+  * Reads a value written by `writeVarIntDelta`, adding the decoded delta to the previous one
+  * (0 at first, after `clear` or after moving the reader index)
+  */
+  readVarIntDelta(): number
+  /** Reads a value written by `writeVarMedium`, rejecting VarInts wider than 24 bits */
+  readVarMedium(): number
+  /** Reads a record written by `writeTlv`, leaving the reader index untouched if it's incomplete */
+  readTlv(): Tlv
+  /** Reads a VarLong, the 64-bit VarInt (at most 10 bytes) */
+  readVarLong(): bigint
+  /** Reads a UTF-8 string prefixed by its length in bytes, VarInt prefixed by default */
+  readString(encoding?: StringEncoding | undefined | null): string
+  /**
+  * Reads a VarInt prefixed UTF-8 string, erroring without advancing if it takes more than
+  * `maxBytes` bytes or has more than `maxChars` code points (e.g. chat length limits)
+  */
+  readStringLimited(maxBytes: number, maxChars: number): string
+  /** Reads a Date stored as a big-endian long of milliseconds since the epoch */
+  readDate(): Date
+  /** Reads `count` big-endian ints in one call */
+  readIntArray(count: number): Int32Array
+  /** Reads `count` floats in the default order in one call */
+  readFloatArray(count: number): Float32Array
+  /** Reads `count` doubles in the default order in one call */
+  readDoubleArray(count: number): Float64Array
+  /** Reads a VarInt enum discriminant, erroring if it isn't less than `max` */
+  readEnum(max: number): number
+  /**
+  * Reads a VarInt length prefixed frame into its own buffer,
+  * or returns null without advancing if the whole frame hasn't arrived yet.
+  * A length over `maxFrameLength` errors straight away instead of waiting for the bytes
+  */
+  readVarIntPrefixedBuf(maxFrameLength?: number | undefined | null): ByteBuf | null
+  /**
+  * Calls `callback` with each complete VarInt length prefixed frame in the readable bytes,
+  * stopping with the reader index at the start of the first incomplete frame.
+  * The reader index moves past a frame before its callback runs, which gets its own copy of
+  * the frame and can't call back into this buffer. Returns the frame count
+  */
+  decodeFrames(callback: (frame: ByteBuf) => void, maxFrameLength?: number | undefined | null): number
+  /**
+  * Checks the next `length` bytes are printable ASCII without consuming them,
+  * the error names the absolute index of the first offending byte
+  */
+  debugAssertAscii(length: number): void
+  /** Reads UTF-16 code units up to a 0x0000 terminator (consumed, not returned) */
+  readUtf16String(littleEndian: boolean): string
+  /** Reads a VarInt count followed by that many VarLongs, erroring if the count exceeds `max` */
+  readVarLongArray(max: number): Array<bigint>
+  /**
+  * Reads a VarInt count followed by that many VarInt prefixed UTF-8 strings,
+  * erroring if the count exceeds `maxCount`
+  */
+  readStringList(maxCount: number): Array<string>
+  /** Reads all the readable bytes, leaving the reader index at the writer index */
+  readRemaining(): Buffer
+  /**
+  * Reads a single value by type name, one of
+  * `byte`, `short`, `int`, `long`, `float`, `double`, `varint` or `bool`
+  */
+  readScalar(typeName: string): number | bigint | boolean
+  /** Reads the next `length` bytes into a new Uint8Array */
+  readIntoUint8Array(length: number): Uint8Array
+  /**
+  * Reads the next `length` bytes as a lowercase hex string,
+  * handy for logging a single field's raw bytes
+  */
+  readHex(length: number): string
+  /**
+  * Reads `length` bytes of packed BCD, two decimal digits per byte high nibble first.
+  * A trailing 0xF pad nibble (odd digit count) is dropped
+  */
+  readBcd(length: number): string
+  /**
+  * Reads `count` (1 to 32) bits, most significant bit first within each byte unless
+  * `setBitOrder(false)` was called. Byte reads after a bit read start at the next whole byte
+  */
+  readBits(count: number): number
+  /**
+  * Reads `count` (1 to 32) bits packed least significant bit first, like deflate.
+  * Keeps its own bit offset, separate from `readBits` even after `setBitOrder(false)`,
+  * so switching between the two mid-byte moves on to the next whole byte
+  */
+  readBitsLE(count: number): number
+  /**
+  * Like `readBits` but for fields of up to 64 bits, returned as a BigInt.
+  * Shares the bit offset and bit order of `readBits`
+  */
+  readBitsWide(count: number): bigint
+  /** Returns the unsigned byte `offset` bytes past the reader index without advancing */
+  peekAt(offset: number): number
+  /**
+  * Returns the absolute index of the first readable byte equal to `value` (truncated to a byte
+  * like `writeByte`), or -1
+  */
+  indexOf(value: number): number
+  /** Returns the absolute index of the last readable byte equal to `value`, or -1 (for trailers) */
+  lastIndexOf(value: number): number
+  /**
+  * Counts the readable bytes from the reader index on for which `predicate` (called with
+  * the unsigned byte) returns truthy, stopping at the first miss. Doesn't advance
+  */
+  countWhile(predicate: (byte: number) => boolean): number
+  /**
+  * Advances the reader index past the leading bytes matching `predicate` (see `countWhile`),
+  * returns how many were skipped
+  */
+  skipWhile(predicate: (byte: number) => boolean): number
+  /**
+  * Reads the next `length` bytes into an owned Buffer, valid after later writes reallocate
+  * the backing store
+  */
+  readBytesCopy(length: number): Buffer
+  /** Reads the next `length` bytes in reverse order, e.g. to flip a little-endian blob */
+  readBytesReversed(length: number): Buffer
+  /**
+  * Decodes all the readable bytes as `utf-8`, `ascii` or `latin1`, leaving the reader index
+  * at the writer index
+  */
+  readStringToEnd(encoding: string): string
+  /**
+  * Reads one value per type name in `spec` (see `readScalar`) in a single call,
+  * rolling the reader index back if any of them fails
+  */
+  readBatch(spec: Array<string>): Array<number | bigint | boolean>
+  /**
+  * Reads a record into an object keyed by field name, each field typed like `readScalar`
+  * plus `string` (VarInt prefixed), rolling the reader index back if any of them fails
+  */
+  readStruct(fields: Array<StructField>): Record<string, number | bigint | boolean | string>
+  /**
+  * Reads a count typed by `countType` (`byte`, `short`, `int` or `varint`, unsigned) followed
+  * by that many records (see `readStruct`), erroring if the count exceeds `maxCount`.
+  * Rolls the reader index back if anything fails
+  */
+  readStructList(countType: string, fields: Array<StructField>, maxCount: number): Array<Record<string, number | bigint | boolean | string>>
+  /**
+  * Returns a copy of the next `length` readable bytes without advancing, for scanning
+  * a header in place. The copy stays valid whatever happens to this buffer afterwards
+  */
+  peekWindow(length: number): Buffer
+  /** Appends all the bytes of a Buffer or a plain Uint8Array */
+  append(data: Buffer | Uint8Array): void
+  /**
+  * Moves `length` readable bytes out of `source` (advancing its reader index) onto the end
+  * of this buffer, for relaying between buffers without a JS copy in between
+  */
+  appendFrom(source: ByteBuf, length: number): void
   writeBoolean(val: boolean): void
   /** Writes both a signed / unsigned byte */
   writeByte(val: number): void
   writeShort(val: number): void
+  /**
+  * Writes a big-endian signed 24-bit int, erroring instead of truncating outside
+  * -8388608..=8388607
+  */
   writeMedium(val: number): void
+  /** Writes a big-endian unsigned 24-bit int, erroring outside 0..=16777215 */
+  writeUnsignedMedium(val: number): void
+  /** Writes a big-endian IEEE-754 half-precision (16-bit) float, rounding to nearest */
+  writeHalf(val: number): void
+  writeInt(val: number): void
+  writeLong(val: bigint): void
+  writeLongLE(val: bigint): void
+  writeUnsignedLong(val: bigint): void
+  writeUnsignedLongLE(val: bigint): void
+  /** Writes a float in the default order, big-endian unless changed */
+  writeFloat(val: number): void
+  /** Writes a double in the default order, big-endian unless changed */
+  writeDouble(val: number): void
+  /** Writes a VarInt, negative values always take 5 bytes. Returns the number of bytes written */
+  writeVarInt(val: number): number
+  /**
+  * Writes an unsigned 24-bit value (0 to 16777215) as a VarInt instead of `writeMedium`'s
+  * fixed 3 bytes: up to 127 takes 1 byte, and since each byte carries 7 bits, values of
+  * 2097152 and up take 4. Returns the number of bytes written
+  */
+  writeVarMedium(val: number): number
+  /** Writes a VarLong, negative values always take 10 bytes. Returns the number of bytes written */
+  writeVarLong(val: bigint): number
+  /**
+  * Writes the zig-zag VarInt of `val` minus the previous value passed here (0 at first, after
+  * `clear` or after moving the writer index), so sorted sequences take a byte or two per value.
+  * A write rolled back, by a transaction or a throwing callback, restores the previous value.
+  * Returns the bytes written
+  */
+  writeVarIntDelta(val: number): number
+  /** Writes a UTF-8 string prefixed by its length in bytes, VarInt prefixed by default */
+  writeString(val: string, encoding?: StringEncoding | undefined | null): void
+  /**
+  * Writes a VarInt prefixed UTF-8 string, erroring without writing anything if it takes
+  * more than `max_bytes` bytes, returns the total bytes written including the prefix
+  */
+  writeStringCapped(val: string, maxBytes: number): number
+  /**
+  * Decodes a base64 string and appends the bytes, returns how many were written.
+  * `urlSafe` switches to the `-_` alphabet, trailing `=` padding is optional either way
+  */
+  writeBase64(s: string, urlSafe?: boolean | undefined | null): number
+  /** Writes `data` prefixed by its length as a VarInt, in one call */
+  writeVarIntBytes(data: Buffer): void
+  /** Writes a tag-length-value record: the tag and the value length as VarInts, then the value */
+  writeTlv(tag: number, value: Buffer): void
+  /**
+  * Writes a digit string as packed BCD, two digits per byte high nibble first,
+  * an odd count is padded with a 0xF nibble
+  */
+  writeBcd(digits: string): void
+  /** Writes a Date as a big-endian long of milliseconds since the epoch */
+  writeDate(date: Date): void
+  /** Writes every element as a big-endian int in one call */
+  writeIntArray(data: Int32Array): void
+  /** Writes every element as a float in the default order in one call */
+  writeFloatArray(data: Float32Array): void
+  /** Writes every element as a double in the default order in one call */
+  writeDoubleArray(data: Float64Array): void
+  /**
+  * Reserves `length` zeroed bytes, advances the writer index past them and
+  * returns a zeroed Uint8Array for the caller to fill. The array is an owned copy,
+  * mutations don't propagate back until it's passed to `commitWritableSlice`
+  */
+  writableSlice(length: number): Uint8Array
+  /** Copies a filled `writableSlice` array into the region it reserved */
+  commitWritableSlice(slice: Uint8Array): void
+  /** Writes an enum discriminant as a VarInt, erroring if it isn't less than `max` */
+  writeEnum(val: number, max: number): void
+  /** Writes a VarInt, erroring instead if its encoding would take more than `max_bytes` */
+  writeVarIntMax(val: number, maxBytes: number): void
+  /** Writes the string as UTF-16 code units followed by a 0x0000 terminator */
+  writeUtf16String(val: string, littleEndian: boolean): void
+  /** Writes a VarInt count followed by every value as a VarLong */
+  writeVarLongArray(vals: Array<bigint>): void
+  /** Writes a VarInt count followed by every string VarInt prefixed */
+  writeStringList(list: Array<string>): void
+  /**
+  * Runs `callback`, rolling the writer index (and written bytes) back if it throws,
+  * so a failed serialization doesn't leave a half-written frame behind
+  */
+  writeTransaction(callback: (...args: any[]) => any): void
+  /**
+  * Runs `callback` to write a body, then prefixes it with its length as a VarInt,
+  * shifting the body over when the length takes more than the 1 byte reserved for it.
+  * Rolls everything back if the callback throws. Returns the body length
+  */
+  writeLengthPrefixedStruct(callback: (...args: any[]) => any): number
+  /**
+  * Registers a callback fired with `(oldCapacity, newCapacity)` whenever a write
+  * makes the backing store reallocate, pass null to remove it
+  */
+  onGrow(callback: ((oldCapacity: number, newCapacity: number) => void) | null): void
+  /**
+  * Registers a callback fired with `readableBytes` whenever a write or a writer index move
+  * takes it from below `threshold` to at least `threshold`, e.g. once a whole frame has arrived.
+  * Pass null to remove it
+  */
+  onReadable(threshold: number, callback: ((readableBytes: number) => void) | null): void
+  /**
+  * Writes a single value by type name, see `readScalar` for the supported names.
+  * `long` expects a BigInt, `bool` a boolean and every other type a number
+  */
+  writeScalar(typeName: string, value: number | bigint | boolean): void
+  swapShortEndianness(index: number, count: number): void
+  swapIntEndianness(index: number, count: number): void
+  swapLongEndianness(index: number, count: number): void
+  /**
+  * Writes the low `count` (1 to 32) bits of `val`, most significant bit first within each byte
+  * unless `setBitOrder(false)` was called. Byte writes after a bit write start on a new byte
+  */
+  writeBits(val: number, count: number): void
+  /**
+  * Like `writeBits` but for fields of up to 64 bits given as a BigInt.
+  * Shares the bit offset and bit order of `writeBits`
+  */
+  writeBitsWide(val: bigint, count: number): void
+  /**
+  * Picks the bit order of `readBits`/`writeBits`, most significant bit first (the default)
+  * or least significant bit first like the `LE` variants. Starts bit accesses over on a
+  * whole byte
+  */
+  setBitOrder(msbFirst: boolean): void
+  /**
+  * Writes the low `count` (1 to 32) bits of `val` least significant bit first, like deflate.
+  * Keeps its own bit offset, separate from `writeBits` even after `setBitOrder(false)`,
+  * so switching between the two mid-byte moves on to a new byte
+  */
+  writeBitsLE(val: number, count: number): void
+  /** Writes zero bytes until the writer index is a multiple of `boundary` */
+  alignWriter(boundary: number): void
+  /**
+  * Appends `count` bytes, each one returned by `producer` called with its index.
+  * Nothing is written if `producer` throws, and it can't call back into this buffer
+  */
+  fill(count: number, producer: (index: number) => number): void
+  /**
+  * Overwrites the big-endian int at the absolute `index` without moving either index,
+  * the int must lie within the written bytes
+  */
+  setInt(index: number, val: number): void
+  /**
+  * Writes each value with the type name at the same position in `spec` (see `writeScalar`)
+  * in a single call, rolling the written bytes back if any of them fails
+  */
+  writeBatch(spec: Array<string>, values: Array<number | bigint | boolean>): void
+  /**
+  * Writes `value`'s fields in the order and with the types given by `fields` (see `readStruct`),
+  * rolling the written bytes back on a missing key or a type mismatch
+  */
+  writeStruct(fields: Array<StructField>, value: Record<string, number | bigint | boolean | string>): void
   setReaderIndex(index: number): void
+  /**
+  * Returns a cursor starting at the reader index, reading it never moves this buffer's
+  * reader index. It reads a snapshot: bytes written afterwards aren't visible to it
+  */
+  cursor(): ByteBufCursor
+  /** Like `setReaderIndex` but also rejects indices past the written bytes */
+  setReaderIndexStrict(index: number): void
   getReaderIndex(): number
+  /**
+  * Moves the readable bytes to the front of the buffer, like Netty's compact.
+  * Unlike a shrinking discard the capacity is left untouched, so the freed
+  * tail is reused by the following writes
+  */
+  compact(): void
+  /**
+  * Netty's heuristic discard: only compacts once the reader index reaches half
+  * the capacity (or everything was read), amortizing the move over many small reads
+  */
+  discardSomeReadBytes(): void
   setWriterIndex(index: number): void
+  /**
+  * When enabled, `setWriterIndex` and `setIndex` past the capacity grow the buffer and
+  * zero-fill the gap instead of erroring. Off by default
+  */
+  setAutoGrow(enabled: boolean): void
+  /**
+  * Like `setWriterIndex` but only within the written bytes, erroring instead of
+  * zero-filling reserved capacity
+  */
+  setWriterIndexStrict(index: number): void
   getWriterIndex(): number
   setIndex(rIndex: number, wIndex: number): void
+  /**
+  * Clamps the indices back into `0 <= readerIndex <= writerIndex <= written length` and returns
+  * whether anything changed. The index setters already keep them there, this is a safety net
+  */
+  normalizeIndices(): boolean
+}
+/** Independent reader over a snapshot of a buffer's written bytes, see `ByteBuf.cursor` */
+export class ByteBufCursor {
+  /** Absolute index of the next byte to read */
+  getPosition(): number
+  setPosition(index: number): void
+  getReadableBytes(): number
+  skipBytes(length: number): void
+  readBoolean(): boolean
+  readByte(): number
+  readUnsignedByte(): number
+  readShort(): number
+  readUnsignedShort(): number
+  readMedium(): number
+  readInt(): number
+  readLong(): bigint
+  readFloat(): number
+  readDouble(): number
+  readVarInt(maxBits?: number | undefined | null): number
+  readString(encoding?: StringEncoding | undefined | null): string
 }
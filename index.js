@@ -246,6 +246,9 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { ByteBuf } = nativeBinding
+const { ByteOrder, StringEncoding, ByteBuf, ByteBufCursor } = nativeBinding
 
+module.exports.ByteOrder = ByteOrder
+module.exports.StringEncoding = StringEncoding
 module.exports.ByteBuf = ByteBuf
+module.exports.ByteBufCursor = ByteBufCursor
@@ -1,279 +1,3326 @@
 #![deny(clippy::all)]
 
-use napi::bindgen_prelude::Uint8Array;
-use napi::{bindgen_prelude::Buffer, Error, Status};
-use napi::Status::GenericFailure;
+use napi::bindgen_prelude::{
+  BigInt, ClassInstance, Date, Either, Float32Array, Float64Array, FunctionRef, Int32Array,
+  JsError, Uint8Array,
+};
+use napi::{
+  bindgen_prelude::Buffer, Env, JsArrayBuffer, JsBigInt, JsBoolean, JsFunction, JsNumber,
+  JsObject, JsString, JsUnknown, Status, ValueType,
+};
+
+use std::cell::Cell;
+use std::mem::MaybeUninit;
 
 #[macro_use]
 extern crate napi_derive;
 
+/// How many readable bytes `inspect` shows before truncating, see `setDebugPreviewLength`
+const INSPECT_PREVIEW_LENGTH: usize = 16;
+
+/// Machine-readable reason attached to every error as its JS `code`,
+/// so callers can branch without matching on the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+  /// Fewer readable bytes than the read needs
+  Underflow,
+  /// A value or length does not fit its encoding
+  Overflow,
+  /// Bytes are not valid UTF-8
+  InvalidUtf8,
+  /// A given argument is out of range, same code as napi's own `InvalidArg`
+  InvalidArg,
+  /// Anything else, including failures bubbled up from napi
+  GenericFailure,
+  /// A JS callback threw, its exception is rethrown untouched
+  PendingException,
+}
+
+impl AsRef<str> for ErrorCode {
+  fn as_ref(&self) -> &str {
+    match self {
+      ErrorCode::Underflow => "UNDERFLOW",
+      ErrorCode::Overflow => "OVERFLOW",
+      ErrorCode::InvalidUtf8 => "INVALID_UTF8",
+      ErrorCode::InvalidArg => "InvalidArg",
+      ErrorCode::GenericFailure => "GenericFailure",
+      ErrorCode::PendingException => "PendingException",
+    }
+  }
+}
+
+type Error = napi::Error<ErrorCode>;
+
+/// Carries a napi failure over into an `ErrorCode` error, keeping its status as the code
+/// when it has a counterpart
+fn from_napi(err: napi::Error) -> Error {
+  let code = match err.status {
+    Status::InvalidArg => ErrorCode::InvalidArg,
+    Status::PendingException => ErrorCode::PendingException,
+    _ => ErrorCode::GenericFailure,
+  };
+  Error::new(code, err.reason)
+}
+
+/// Rethrows a failed JS call's exception as-is, `PendingException` tells napi to leave it be
+fn rethrow(env: Env, err: napi::Error) -> Error {
+  let exception = JsError::from(err).into_unknown(env);
+  match env.throw(exception) {
+    Ok(()) => Error::new(ErrorCode::PendingException, String::new()),
+    Err(e) => from_napi(e),
+  }
+}
+
+/// Error for a call back into a buffer from its running `callback` that the callback doesn't allow
+fn in_use(method: &str, callback: &str) -> Error {
+  Error::new(
+    ErrorCode::GenericFailure,
+    format!("cannot {}, buffer is in use by {}", method, callback),
+  )
+}
+
+/// Byte order of the float and double accessors, see `ByteBuf.setDefaultOrder`
+#[napi]
+#[derive(PartialEq, Eq)]
+pub enum ByteOrder {
+  /// Most significant byte first, the default
+  BigEndian,
+  /// Least significant byte first
+  LittleEndian,
+}
+
+/// How the byte length of a string is prefixed
+#[napi]
+pub enum StringEncoding {
+  /// VarInt length prefix
+  VarInt,
+  /// Big-endian u16 length prefix
+  UnsignedShort,
+  /// Big-endian u32 length prefix
+  UnsignedInt,
+}
+
+/// Lengths and indices cross the napi boundary as u32,
+/// buffers past 4 GiB get an error instead of a silently truncated value
+fn to_u32(val: usize, name: &str) -> Result<u32, Error> {
+  u32::try_from(val).map_err(|_| {
+    Error::new(
+      ErrorCode::Overflow,
+      format!("cannot get {}, {} is greater than {}", name, val, u32::MAX),
+    )
+  })
+}
+
+/// Like `to_u32` for the i32 indices of the search methods, which keep -1 for not found
+fn to_i32(val: usize, name: &str) -> Result<i32, Error> {
+  i32::try_from(val).map_err(|_| {
+    Error::new(
+      ErrorCode::Overflow,
+      format!("cannot get {}, {} is greater than {}", name, val, i32::MAX),
+    )
+  })
+}
+
+/// Converts a BigInt to u64, `method` is used in the error message if it doesn't fit
+fn bigint_to_u64(val: &BigInt, method: &str) -> Result<u64, Error> {
+  match val.get_u64() {
+    (false, v, true) => Ok(v),
+    _ => Err(Error::new(
+      ErrorCode::InvalidArg,
+      format!("cannot {}, given BigInt does not fit in an unsigned long", method),
+    )),
+  }
+}
+
+/// Number of bytes the VarInt encoding of `val` takes
+fn var_int_len(val: i32) -> usize {
+  match val as u32 {
+    0..=0x7F => 1,
+    0x80..=0x3FFF => 2,
+    0x4000..=0x1F_FFFF => 3,
+    0x20_0000..=0xFFF_FFFF => 4,
+    _ => 5,
+  }
+}
+
+/// VarInt encoding of `val` and how many of the 5 bytes it takes
+fn encode_var_int(val: i32) -> ([u8; 5], usize) {
+  let mut bytes = [0u8; 5];
+  let mut len = 0;
+  let mut val = val as u32;
+  loop {
+    if val & !0x7F == 0 {
+      bytes[len] = val as u8;
+      len += 1;
+      break;
+    }
+    bytes[len] = (val & 0x7F | 0x80) as u8;
+    len += 1;
+    val >>= 7;
+  }
+  (bytes, len)
+}
+
+/// Converts a BigInt to i64, `method` is used in the error message if it doesn't fit
+fn bigint_to_i64(val: &BigInt, method: &str) -> Result<i64, Error> {
+  match val.get_i64() {
+    (v, true) => Ok(v),
+    _ => Err(Error::new(
+      ErrorCode::InvalidArg,
+      format!("cannot {}, given BigInt does not fit in a signed long", method),
+    )),
+  }
+}
+
+/// Widens an IEEE-754 binary16 to f32, exactly
+fn half_to_f32(half: u16) -> f32 {
+  let sign = ((half as u32) & 0x8000) << 16;
+  let exp = ((half >> 10) & 0x1F) as u32;
+  let mant = (half & 0x3FF) as u32;
+  match exp {
+    0 if mant == 0 => f32::from_bits(sign),
+    // Subnormal, mant * 2^-24
+    0 => {
+      let val = mant as f32 * f32::powi(2.0, -24);
+      if sign != 0 {
+        -val
+      } else {
+        val
+      }
+    }
+    0x1F => f32::from_bits(sign | 0x7F80_0000 | (mant << 13)),
+    _ => f32::from_bits(sign | ((exp + 112) << 23) | (mant << 13)),
+  }
+}
+
+/// Narrows f32 to an IEEE-754 binary16, rounding to nearest even
+fn f32_to_half(val: f32) -> u16 {
+  let bits = val.to_bits();
+  let sign = ((bits >> 16) & 0x8000) as u16;
+  let exp = ((bits >> 23) & 0xFF) as i32;
+  let mant = bits & 0x7F_FFFF;
+  if exp == 0xFF {
+    // Keep NaNs quiet
+    return sign | 0x7C00 | if mant != 0 { 0x200 } else { 0 };
+  }
+  let exp = exp - 127 + 15;
+  if exp >= 0x1F {
+    return sign | 0x7C00;
+  }
+  if exp <= 0 {
+    if exp < -10 {
+      return sign;
+    }
+    let mant = mant | 0x80_0000;
+    let shift = (14 - exp) as u32;
+    let half = mant >> shift;
+    let rem = mant & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    let round = (rem > halfway || (rem == halfway && half & 1 == 1)) as u32;
+    return sign | (half + round) as u16;
+  }
+  let half = ((exp as u32) << 10) | (mant >> 13);
+  let rem = mant & 0x1FFF;
+  // A carry out of the mantissa correctly bumps the exponent (up to infinity)
+  let round = (rem > 0x1000 || (rem == 0x1000 && half & 1 == 1)) as u32;
+  sign | (half + round) as u16
+}
+
+/// All the positional state of a buffer, see `ByteBuf.indices`
+#[napi(object)]
+pub struct ByteBufIndices {
+  pub reader_index: u32,
+  pub writer_index: u32,
+  pub readable_bytes: u32,
+  pub capacity: u32,
+}
+
+/// One named field of a record, see `ByteBuf.readStruct`
+#[napi(object)]
+pub struct StructField {
+  pub name: String,
+  /// A `readScalar` type name or `string`
+  #[napi(js_name = "type")]
+  pub type_name: String,
+}
+
+/// One tag-length-value record, see `ByteBuf.readTlv`
+#[napi(object)]
+pub struct Tlv {
+  pub tag: u32,
+  pub value: Buffer,
+}
+
+/// `(oldCapacity, newCapacity)`, see `ByteBuf.onGrow`
+type GrowCallback = FunctionRef<(u32, u32), JsUnknown>;
+
+/// `(readableBytes)`, see `ByteBuf.onReadable`
+type ReadableCallback = FunctionRef<u32, JsUnknown>;
+
+/// Byte being read (or filled) bit by bit and how many of its bits are used,
+/// only valid while it's still the byte right before the reader (or writer) index
+#[derive(Clone, Copy)]
+struct BitOffset {
+  index: usize,
+  used: u32,
+}
+
+/// Indices, written bytes past the writer index and the rest of the per-write state,
+/// taken before a write that may be rolled back
+struct WriteMark {
+  r_pos: usize,
+  w_pos: usize,
+  len: usize,
+  tail: Vec<u8>,
+  write_delta: i32,
+  read_delta: i32,
+  pending_slice: Option<(usize, usize)>,
+  bit_offsets: [Option<BitOffset>; 4],
+}
+
+/// JS callback this buffer is running and what it may still do to the buffer meanwhile
+#[derive(Clone, Copy)]
+enum CallbackScope {
+  /// Nothing, calls back in would alias the borrow held by the caller
+  Exclusive(&'static str),
+  /// Writes and reads, which a rollback can undo, but nothing moving the writer index
+  /// or the backing store by hand
+  Writes(&'static str),
+}
+
 #[napi]
 pub struct ByteBuf {
   buf: Vec<u8>,
   r_pos: usize,
   w_pos: usize,
+  read_only: bool,
+  auto_grow: bool,
+  zero_on_clear: bool,
+  normalize_capacity: bool,
+  released: bool,
+  max_capacity: Option<usize>,
+  default_order: ByteOrder,
+  order_locked: bool,
+  grow_callback: Option<(Env, GrowCallback)>,
+  readable_callback: Option<(Env, u32, ReadableCallback)>,
+  preview_length: usize,
+  bits_lsb_first: bool,
+  // readBits/writeBits and their LE variants each keep their own offset, whatever the bit order
+  read_bit: Option<BitOffset>,
+  write_bit: Option<BitOffset>,
+  read_bit_le: Option<BitOffset>,
+  write_bit_le: Option<BitOffset>,
+  // Last values seen by writeVarIntDelta / readVarIntDelta
+  write_delta: i32,
+  read_delta: i32,
+  // Start and length of the region reserved by the last writableSlice
+  pending_slice: Option<(usize, usize)>,
+  // JS callback running right now, calls back into this buffer it doesn't allow are rejected
+  in_callback: Cell<Option<CallbackScope>>,
 }
 
 #[napi]
 impl ByteBuf {
   #[napi(constructor)]
   pub fn new(buf: Option<Buffer>) -> Self {
-    let vec = buf.map_or(Vec::new(), |b| b.to_vec());
+    ByteBuf::from_vec(buf.map_or(Vec::new(), |b| b.to_vec()))
+  }
+
+  #[napi(factory)]
+  pub fn with_initial_capacity(initial_capacity: u32) -> Self {
+    ByteBuf::from_vec(Vec::with_capacity(initial_capacity as usize))
+  }
+
+  /// Creates a buffer of exactly `size` zero bytes, all of them written,
+  /// so absolute setters can patch any offset straight away
+  #[napi(factory)]
+  pub fn allocate(size: u32) -> Self {
+    ByteBuf::from_vec(vec![0; size as usize])
+  }
+
+  /// Wraps a copy of the given bytes. The capacity is trimmed to the length, so without
+  /// auto grow `setWriterIndex` can't move past the given bytes
+  #[napi(factory)]
+  pub fn from_byte_array(mut byte_array: Vec<u8>) -> Self {
+    byte_array.shrink_to_fit();
+    ByteBuf::from_vec(byte_array)
+  }
+
+  /// Creates a buffer holding a copy of an ArrayBuffer's bytes, all of them written
+  #[napi(factory)]
+  pub fn from_array_buffer(array_buffer: JsArrayBuffer) -> Result<Self, Error> {
+    let array_buffer = array_buffer.into_value().map_err(from_napi)?;
+    Ok(ByteBuf::from_vec(array_buffer.to_vec()))
+  }
+
+  /// Creates a new buffer holding the readable bytes of all the given buffers, in order
+  #[napi(factory)]
+  pub fn concat(buffers: Vec<ClassInstance<ByteBuf>>) -> Self {
+    let total = buffers.iter().map(|b| b.w_pos - b.r_pos).sum();
+    let mut vec = Vec::with_capacity(total);
+    for b in buffers.iter() {
+      vec.extend_from_slice(&b.buf[b.r_pos..b.w_pos]);
+    }
+    ByteBuf::from_vec(vec)
+  }
+
+  /// How many bytes `writeVarInt` would take for `val`, for precomputing frame sizes
+  #[napi]
+  pub fn var_int_size(val: i32) -> u32 {
+    var_int_len(val) as u32
+  }
+
+  /// How many bytes `writeVarLong` would take for `val`
+  #[napi]
+  pub fn var_long_size(val: BigInt) -> Result<u32, Error> {
+    let val = bigint_to_i64(&val, "varLongSize")? as u64;
+    Ok((64 - val.leading_zeros()).div_ceil(7).max(1))
+  }
+
+  /// Wraps `vec` with the reader index at 0 and the writer index at its end
+  fn from_vec(vec: Vec<u8>) -> Self {
     ByteBuf {
       w_pos: vec.len(),
       buf: vec,
       r_pos: 0,
+      read_only: false,
+      auto_grow: false,
+      zero_on_clear: false,
+      normalize_capacity: false,
+      released: false,
+      max_capacity: None,
+      default_order: ByteOrder::BigEndian,
+      order_locked: false,
+      in_callback: Cell::new(None),
+      grow_callback: None,
+      readable_callback: None,
+      preview_length: INSPECT_PREVIEW_LENGTH,
+      bits_lsb_first: false,
+      read_bit: None,
+      write_bit: None,
+      read_bit_le: None,
+      write_bit_le: None,
+      write_delta: 0,
+      read_delta: 0,
+      pending_slice: None,
     }
   }
 
-  #[napi(factory)]
-  pub fn with_initial_capacity(initial_capacity: u32) -> Self {
-    ByteBuf {
-      buf: Vec::with_capacity(initial_capacity as usize),
-      r_pos: 0,
-      w_pos: 0,
+  /// Moving both indices by hand starts bit accesses over on a whole byte
+  fn reset_bit_offsets(&mut self) {
+    self.reset_read_bit_offsets();
+    self.reset_write_bit_offsets();
+  }
+
+  /// Moving the reader index by hand starts bit reads over on a whole byte
+  fn reset_read_bit_offsets(&mut self) {
+    self.read_bit = None;
+    self.read_bit_le = None;
+  }
+
+  /// Moving the writer index by hand starts bit writes over on a whole byte
+  fn reset_write_bit_offsets(&mut self) {
+    self.write_bit = None;
+    self.write_bit_le = None;
+  }
+
+  #[napi]
+  pub fn clear(&mut self) -> Result<(), Error> {
+    self.ensure_live("clear")?;
+    self.ensure_stable("clear")?;
+    self.reset();
+    Ok(())
+  }
+
+  /// Empties the buffer and starts the indices, bit offsets and deltas over
+  fn reset(&mut self) {
+    if self.zero_on_clear {
+      self.buf.fill(0);
+      self.buf.spare_capacity_mut().fill(MaybeUninit::new(0));
     }
+    self.buf.clear();
+    self.r_pos = 0;
+    self.w_pos = 0;
+    self.reset_bit_offsets();
+    self.write_delta = 0;
+    self.read_delta = 0;
+    self.pending_slice = None;
   }
 
-  #[napi(factory)]
-  pub fn from_byte_array(byte_array: Vec<u8>) -> Self {
-    ByteBuf {
-      w_pos: byte_array.len(),
-      buf: byte_array,
-      r_pos: 0,
+  /// Frees the backing store right away instead of waiting for GC, any later call touching the
+  /// bytes or the indices errors. Calling it again does nothing
+  #[napi]
+  pub fn release(&mut self) -> Result<(), Error> {
+    self.ensure_stable("release")?;
+    if self.released {
+      return Ok(());
+    }
+    self.reset();
+    self.buf = Vec::new();
+    self.grow_callback = None;
+    self.readable_callback = None;
+    self.released = true;
+    Ok(())
+  }
+
+  fn ensure_live(&self, method: &str) -> Result<(), Error> {
+    self.ensure_idle(method)?;
+    if self.released {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        format!("cannot {}, buffer released", method),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Errors if called back from a JS callback this buffer is running that doesn't allow it
+  fn ensure_idle(&self, method: &str) -> Result<(), Error> {
+    if let Some(CallbackScope::Exclusive(callback)) = self.in_callback.get() {
+      return Err(in_use(method, callback));
+    }
+    Ok(())
+  }
+
+  /// Like `ensure_idle` but also errors from callbacks allowed to write, for calls moving the
+  /// writer index or the backing store by hand, which the rollback after a throw can't undo
+  fn ensure_stable(&self, method: &str) -> Result<(), Error> {
+    if let Some(CallbackScope::Exclusive(callback) | CallbackScope::Writes(callback)) =
+      self.in_callback.get()
+    {
+      return Err(in_use(method, callback));
+    }
+    Ok(())
+  }
+
+  /// Runs `f`, which calls into JS, with calls back into this buffer rejected
+  fn guard_callback<T>(&self, callback: &'static str, f: impl FnOnce() -> T) -> T {
+    self.scoped(CallbackScope::Exclusive(callback), f)
+  }
+
+  /// Runs `f`, which calls into JS, with only writes and reads allowed back into this buffer
+  fn guard_writes<T>(&self, callback: &'static str, f: impl FnOnce() -> T) -> T {
+    self.scoped(CallbackScope::Writes(callback), f)
+  }
+
+  fn scoped<T>(&self, scope: CallbackScope, f: impl FnOnce() -> T) -> T {
+    let outer = self.in_callback.replace(Some(scope));
+    let res = f();
+    self.in_callback.set(outer);
+    res
+  }
+
+  /// When enabled, `clear` overwrites the whole backing store with zeros first so sensitive
+  /// data isn't left behind in memory. Off by default
+  #[napi]
+  pub fn set_zero_on_clear(&mut self, enabled: bool) {
+    self.zero_on_clear = enabled;
+  }
+
+  /// Returns the number of bytes this buffer can contain
+  #[napi]
+  pub fn get_capacity(&self) -> Result<u32, Error> {
+    self.ensure_live("getCapacity")?;
+    to_u32(self.buf.capacity(), "capacity")
+  }
+
+  /// u32 is enough, i64 is too much even for general use
+  #[napi]
+  pub fn set_capacity(&mut self, size: u32) -> Result<(), Error> {
+    self.ensure_writable("setCapacity")?;
+    self.ensure_stable("setCapacity")?;
+    if (size as usize) < self.buf.capacity() {
+      self.buf.truncate(size as usize);
+      self.buf.shrink_to(size as usize);
+      self.w_pos = self.w_pos.min(self.buf.len());
+      self.r_pos = self.r_pos.min(self.w_pos);
+      self.reset_bit_offsets();
+      return Ok(());
+    }
+    // TODO: might be better to use try_reserve_exact
+    self.buf.reserve_exact(size as usize - self.buf.capacity());
+    Ok(())
+  }
+
+  /// When enabled, growing rounds the capacity up to the next power of two like Netty does,
+  /// so buffers written in small steps reallocate less often. Off by default
+  #[napi]
+  pub fn set_capacity_normalization(&mut self, enabled: bool) {
+    self.normalize_capacity = enabled;
+  }
+
+  /// Makes room for `additional` bytes past the written length, honoring capacity normalization
+  fn reserve(&mut self, additional: usize) {
+    let needed = self.buf.len() + additional;
+    if self.normalize_capacity && needed > self.buf.capacity() {
+      self.buf.reserve_exact(needed.next_power_of_two() - self.buf.len());
+    } else {
+      self.buf.reserve(additional);
+    }
+  }
+
+  /// Caps how far writes can move the writer index, writes past it error instead of growing.
+  /// Unbounded (null) by default
+  #[napi]
+  pub fn set_max_capacity(&mut self, max_capacity: Option<u32>) -> Result<(), Error> {
+    if let Some(max) = max_capacity.filter(|&max| (max as usize) < self.w_pos) {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot setMaxCapacity, given maxCapacity {} is less than writerIndex {}",
+          max, self.w_pos
+        ),
+      ));
+    }
+    self.max_capacity = max_capacity.map(|max| max as usize);
+    Ok(())
+  }
+
+  #[napi]
+  pub fn get_max_capacity(&self) -> Option<u32> {
+    self.max_capacity.map(|max| max as u32)
+  }
+
+  /// Whether the writer index reached the max capacity, never true when unbounded
+  #[napi]
+  pub fn is_full(&self) -> bool {
+    self.max_capacity.is_some_and(|max| self.w_pos >= max)
+  }
+
+  /// Errors if writing `length` more bytes would move the writer index past the max capacity
+  fn check_max_capacity(&self, length: usize) -> Result<(), Error> {
+    match self.max_capacity {
+      Some(max) if self.w_pos + length > max => Err(Error::new(
+        ErrorCode::Overflow,
+        format!(
+          "cannot write {} bytes, writerIndex {} would exceed maxCapacity {}",
+          length, self.w_pos, max
+        ),
+      )),
+      _ => Ok(()),
+    }
+  }
+
+  /// Sets the byte order of `readFloat`/`readDouble`, `getFloat`/`getDouble`
+  /// and `writeFloat`/`writeDouble`, every other accessor has a fixed order.
+  /// Errors once `lockOrder` was called
+  #[napi]
+  pub fn set_default_order(&mut self, order: ByteOrder) -> Result<(), Error> {
+    if self.order_locked {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        "cannot setDefaultOrder, byte order is locked".to_string(),
+      ));
+    }
+    self.default_order = order;
+    Ok(())
+  }
+
+  /// Sets the default order for good, so a wire format built on this buffer can't be changed
+  /// by downstream code: `setDefaultOrder` errors afterwards, and so does locking another order
+  #[napi]
+  pub fn lock_order(&mut self, order: ByteOrder) -> Result<(), Error> {
+    if self.order_locked && self.default_order != order {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        "cannot lockOrder, byte order is already locked to another order".to_string(),
+      ));
+    }
+    self.default_order = order;
+    self.order_locked = true;
+    Ok(())
+  }
+
+  #[napi]
+  pub fn get_default_order(&self) -> ByteOrder {
+    self.default_order
+  }
+
+  #[napi]
+  pub fn is_read_only(&self) -> bool {
+    self.read_only
+  }
+
+  /// Returns a read-only copy of the readable bytes with its own reader index,
+  /// so a consumer can parse it while this buffer keeps accumulating writes
+  #[napi]
+  pub fn readable_window(&self) -> Result<ByteBuf, Error> {
+    self.ensure_live("readableWindow")?;
+    let mut window = ByteBuf::from_vec(self.buf[self.r_pos..self.w_pos].to_vec());
+    window.read_only = true;
+    Ok(window)
+  }
+
+  /// Splits the written bytes into two independent copies, `[0, index)` and
+  /// `[index, writerIndex)`, e.g. a header and its body
+  #[napi(ts_return_type = "[ByteBuf, ByteBuf]")]
+  pub fn split(&self, index: u32) -> Result<Vec<ByteBuf>, Error> {
+    self.ensure_live("split")?;
+    let index = index as usize;
+    if index > self.w_pos {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot split, given index {} is greater than writerIndex {}",
+          index, self.w_pos
+        ),
+      ));
+    }
+    Ok(vec![
+      ByteBuf::from_vec(self.buf[..index].to_vec()),
+      ByteBuf::from_vec(self.buf[index..self.w_pos].to_vec()),
+    ])
+  }
+
+  /// Returns `n` independent buffers each holding a copy of the readable bytes, with their
+  /// reader index at 0, to parse the same payload several ways
+  #[napi]
+  pub fn readers(&self, n: u32) -> Result<Vec<ByteBuf>, Error> {
+    self.ensure_live("readers")?;
+    let readable = &self.buf[self.r_pos..self.w_pos];
+    Ok(
+      (0..n)
+        .map(|_| {
+          let mut reader = ByteBuf::from_vec(readable.to_vec());
+          reader.default_order = self.default_order;
+          reader
+        })
+        .collect(),
+    )
+  }
+
+  /// Returns a copy of the written bytes positioned at the given indices, in one step instead
+  /// of copying and then calling `setIndex`. Requires `reader <= writer <= written length`
+  #[napi]
+  pub fn duplicate_with_indices(&self, reader: u32, writer: u32) -> Result<ByteBuf, Error> {
+    self.ensure_live("duplicateWithIndices")?;
+    if reader > writer {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot duplicateWithIndices, given writerIndex {} is less than given readerIndex {}",
+          writer, reader
+        ),
+      ));
+    }
+    self.check_written("duplicateWithIndices", "writerIndex", writer)?;
+    let mut dup = ByteBuf::from_vec(self.buf.clone());
+    dup.r_pos = reader as usize;
+    dup.w_pos = writer as usize;
+    dup.default_order = self.default_order;
+    Ok(dup)
+  }
+
+  /// Involves copying, use with caution
+  #[napi]
+  pub fn get_array(&self) -> Result<Uint8Array, Error> {
+    self.ensure_live("getArray")?;
+    Ok(Uint8Array::new(self.buf.clone()))
+  }
+
+  /// Returns a copy of the readable bytes, safe to keep across writes
+  #[napi]
+  pub fn get_buffer(&self) -> Result<Buffer, Error> {
+    self.ensure_live("getBuffer")?;
+    Ok(Buffer::from(&self.buf[self.r_pos..self.w_pos]))
+  }
+
+  /// Returns a copy of the whole readable region without advancing, same as `getBuffer`
+  /// but named for inspecting next to the advancing reads
+  #[napi]
+  pub fn peek_all(&self) -> Result<Buffer, Error> {
+    self.ensure_live("peekAll")?;
+    self.get_buffer()
+  }
+
+  /// Returns a copy of everything written so far, `[0, writerIndex)`,
+  /// including bytes already read
+  #[napi]
+  pub fn to_full_buffer(&self) -> Result<Buffer, Error> {
+    self.ensure_live("toFullBuffer")?;
+    Ok(Buffer::from(&self.buf[..self.w_pos]))
+  }
+
+  /// Returns the readable bytes as an ArrayBuffer, for APIs that don't accept a Buffer.
+  /// Zero-copy: the backing store is handed over to the ArrayBuffer, which frees it once
+  /// collected, and this buffer is left empty with no capacity, like after `clear` on a fresh
+  /// buffer. The two never alias, later writes go to a new backing store
+  #[napi]
+  pub fn to_array_buffer(&mut self, env: Env) -> Result<JsArrayBuffer, Error> {
+    self.ensure_writable("toArrayBuffer")?;
+    self.ensure_stable("toArrayBuffer")?;
+    let (r_pos, w_pos) = (self.r_pos, self.w_pos);
+    let mut vec = std::mem::take(&mut self.buf);
+    self.reset();
+    // Safety: the finalizer owns `vec`, so the bytes stay put until the ArrayBuffer is collected
+    unsafe {
+      let data = vec.as_mut_ptr().add(r_pos);
+      env.create_arraybuffer_with_borrowed_data(data, w_pos - r_pos, vec, |vec, _| drop(vec))
+    }
+    .map(|ab| ab.into_raw())
+    .map_err(from_napi)
+  }
+
+  /// Copies the readable bytes into `target` starting at `offset` without consuming them,
+  /// returns the number of bytes copied
+  #[napi]
+  pub fn copy_to_array_buffer(&self, target: JsArrayBuffer, offset: u32) -> Result<u32, Error> {
+    self.ensure_live("copyToArrayBuffer")?;
+    let mut target = target.into_value().map_err(from_napi)?;
+    let start = offset as usize;
+    let length = self.readable_bytes();
+    if start + length > target.len() {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot copyToArrayBuffer, given offset {} + readableBytes {} exceeds byteLength {}",
+          offset,
+          length,
+          target.len()
+        ),
+      ));
+    }
+    target[start..start + length].copy_from_slice(&self.buf[self.r_pos..self.w_pos]);
+    to_u32(length, "copied bytes")
+  }
+
+  /// Errors rather than truncating if the count doesn't fit in a u32
+  #[napi]
+  pub fn get_readable_bytes(&self) -> Result<u32, Error> {
+    self.ensure_live("getReadableBytes")?;
+    to_u32(self.readable_bytes(), "readableBytes")
+  }
+
+  /// The exact readable count as a BigInt, for buffers too large for `getReadableBytes`
+  #[napi]
+  pub fn readable_bytes_usize(&self) -> Result<BigInt, Error> {
+    self.ensure_live("readableBytesUsize")?;
+    Ok(BigInt::from(self.readable_bytes() as u64))
+  }
+
+  fn readable_bytes(&self) -> usize {
+    self.w_pos - self.r_pos
+  }
+
+  /// Error for a read of `needed` bytes with fewer readable, naming what was available
+  /// and where, `method` is used in the error message
+  fn underflow(&self, method: &str, needed: usize) -> Error {
+    Error::new(
+      ErrorCode::Underflow,
+      format!(
+        "cannot {}, readableBytes {} is less than {} at readerIndex {}",
+        method,
+        self.readable_bytes(),
+        needed,
+        self.r_pos
+      ),
+    )
+  }
+
+  /// Decodes all the readable bytes as UTF-8 without advancing the reader index
+  #[napi]
+  pub fn to_utf8_string(&self) -> Result<String, Error> {
+    self.ensure_live("toUtf8String")?;
+    std::str::from_utf8(&self.buf[self.r_pos..self.w_pos])
+      .map(|val| val.to_string())
+      .map_err(|e| {
+        Error::new(
+          ErrorCode::InvalidUtf8,
+          format!("cannot toUtf8String, invalid UTF-8: {}", e),
+        )
+      })
+  }
+
+  /// Returns reader / writer index, readable bytes and capacity in a single call
+  #[napi]
+  pub fn indices(&self) -> Result<ByteBufIndices, Error> {
+    self.ensure_live("indices")?;
+    Ok(ByteBufIndices {
+      reader_index: self.get_reader_index()?,
+      writer_index: self.get_writer_index()?,
+      readable_bytes: self.get_readable_bytes()?,
+      capacity: self.get_capacity()?,
+    })
+  }
+
+  /// 32-bit FNV-1a hash of the readable bytes, so like Netty's `hashCode` it only depends
+  /// on their content and not on the indices or capacity
+  #[napi]
+  pub fn hash_code(&self) -> Result<i32, Error> {
+    self.ensure_live("hashCode")?;
+    Ok(
+      self.buf[self.r_pos..self.w_pos]
+        .iter()
+        .fold(0x811c_9dc5u32, |hash, &b| (hash ^ b as u32).wrapping_mul(0x0100_0193)) as i32,
+    )
+  }
+
+  /// Sets how many readable bytes `inspect` previews, 16 by default
+  #[napi]
+  pub fn set_debug_preview_length(&mut self, length: u32) {
+    self.preview_length = length as usize;
+  }
+
+  /// Compact summary of the indices plus a hex preview of the readable bytes
+  #[napi]
+  pub fn inspect(&self) -> Result<String, Error> {
+    self.ensure_live("inspect")?;
+    let readable = &self.buf[self.r_pos..self.w_pos];
+    let preview = readable
+      .iter()
+      .take(self.preview_length)
+      .map(|b| format!("{:02x}", b))
+      .collect::<Vec<String>>()
+      .join(" ");
+    Ok(format!(
+      "ByteBuf(readerIndex={}, writerIndex={}, capacity={}, readable={}) [{}{}]",
+      self.r_pos,
+      self.w_pos,
+      self.buf.capacity(),
+      readable.len(),
+      preview,
+      if readable.len() > self.preview_length {
+        " ..."
+      } else {
+        ""
+      }
+    ))
+  }
+
+  #[napi]
+  pub fn skip_bytes(&mut self, length: u32) -> Result<(), Error> {
+    self.ensure_live("skipBytes")?;
+    if length as usize > self.readable_bytes() {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot skipBytes, given length {} is greater than readableBytes {}",
+          length,
+          self.readable_bytes()
+        ),
+      ));
+    }
+    self.r_pos += length as usize;
+    Ok(())
+  }
+
+  /// Skips at most `length` bytes, clamped to readableBytes, and returns how many were skipped
+  #[napi]
+  pub fn skip_up_to(&mut self, length: u32) -> Result<u32, Error> {
+    self.ensure_live("skipUpTo")?;
+    let skipped = self.readable_bytes().min(length as usize);
+    self.r_pos += skipped;
+    Ok(skipped as u32)
+  }
+
+  /// Number of bytes from `pos` up to the next multiple of `boundary`, `method` is used in the
+  /// error message
+  fn padding_to(pos: usize, boundary: u32, method: &str) -> Result<usize, Error> {
+    if boundary == 0 {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!("cannot {}, given boundary is 0", method),
+      ));
+    }
+    let boundary = boundary as usize;
+    Ok((boundary - pos % boundary) % boundary)
+  }
+
+  /// Skips padding until the reader index is a multiple of `boundary`
+  #[napi]
+  pub fn align_reader(&mut self, boundary: u32) -> Result<(), Error> {
+    self.ensure_live("alignReader")?;
+    let padding = Self::padding_to(self.r_pos, boundary, "alignReader")?;
+    if self.readable_bytes() < padding {
+      return Err(self.underflow("alignReader", padding));
+    }
+    self.r_pos += padding;
+    Ok(())
+  }
+
+  // READ METHODS
+
+  #[napi]
+  #[allow(clippy::redundant_pattern_matching)]
+  pub fn read_boolean(&mut self) -> Result<bool, Error> {
+    self.ensure_live("readBoolean")?;
+    // TODO: Waiting for stable is_ok_and
+    let res = self.read_byte();
+    if let Ok(..) = res {
+      return Ok(res.unwrap() != 0);
+    }
+    Err(self.underflow("readBoolean", 1))
+  }
+
+  #[napi]
+  pub fn read_byte(&mut self) -> Result<i32, Error> {
+    self.ensure_live("readByte")?;
+    if self.readable_bytes() < 1 {
+      return Err(self.underflow("readByte", 1));
+    }
+    self.r_pos += 1;
+    Ok(self.buf[self.r_pos - 1] as i8 as i32)
+  }
+
+  #[napi]
+  pub fn read_unsigned_byte(&mut self) -> Result<u32, Error> {
+    self.ensure_live("readUnsignedByte")?;
+    if self.readable_bytes() < 1 {
+      return Err(self.underflow("readUnsignedByte", 1));
+    }
+    self.r_pos += 1;
+    Ok(self.buf[self.r_pos - 1] as u32)
+  }
+
+  #[napi]
+  pub fn read_short(&mut self) -> Result<i32, Error> {
+    self.ensure_live("readShort")?;
+    if self.readable_bytes() < 2 {
+      return Err(self.underflow("readShort", 2));
+    }
+    self.r_pos += 2;
+    Ok(i16::from_be_bytes(
+      self.buf[self.r_pos - 2..self.r_pos]
+        .try_into()
+        .unwrap(),
+    ) as i32)
+  }
+
+  #[napi(js_name = "readShortLE")]
+  pub fn read_short_le(&mut self) -> Result<i32, Error> {
+    self.ensure_live("readShortLE")?;
+    if self.readable_bytes() < 2 {
+      return Err(self.underflow("readShortLE", 2));
+    }
+    self.r_pos += 2;
+    Ok(i16::from_le_bytes(
+      self.buf[self.r_pos - 2..self.r_pos]
+        .try_into()
+        .unwrap(),
+    ) as i32)
+  }
+
+  #[napi]
+  pub fn read_unsigned_short(&mut self) -> Result<u32, Error> {
+    self.ensure_live("readUnsignedShort")?;
+    if self.readable_bytes() < 2 {
+      return Err(self.underflow("readUnsignedShort", 2));
+    }
+    self.r_pos += 2;
+    Ok(u16::from_be_bytes(
+      self.buf[self.r_pos - 2..self.r_pos]
+        .try_into()
+        .unwrap(),
+    ) as u32)
+  }
+
+  #[napi(js_name = "readUnsignedShortLE")]
+  pub fn read_unsigned_short_le(&mut self) -> Result<u32, Error> {
+    self.ensure_live("readUnsignedShortLE")?;
+    if self.readable_bytes() < 2 {
+      return Err(self.underflow("readUnsignedShortLE", 2));
+    }
+    self.r_pos += 2;
+    Ok(u16::from_le_bytes(
+      self.buf[self.r_pos - 2..self.r_pos]
+        .try_into()
+        .unwrap(),
+    ) as u32)
+  }
+
+  /// Reads the next 3 bytes as an unsigned 24-bit integer, `method` is used in the error message
+  fn read_u24(&mut self, method: &str, little_endian: bool) -> Result<u32, Error> {
+    if self.readable_bytes() < 3 {
+      return Err(self.underflow(method, 3));
+    }
+    self.r_pos += 3;
+    let res = &self.buf[(self.r_pos - 3)..self.r_pos];
+    if little_endian {
+      Ok(res[0] as u32 | ((res[1] as u32) << 8) | ((res[2] as u32) << 16))
+    } else {
+      Ok(((res[0] as u32) << 16) | ((res[1] as u32) << 8) | res[2] as u32)
+    }
+  }
+
+  /// Reads a signed (sign-extended) 24-bit integer
+  #[napi]
+  pub fn read_medium(&mut self) -> Result<i32, Error> {
+    self.ensure_live("readMedium")?;
+    self
+      .read_u24("readMedium", false)
+      .map(|v| ((v << 8) as i32) >> 8)
+  }
+
+  /// Same as `readMedium`, named after its signedness
+  #[napi]
+  pub fn read_int24_signed(&mut self) -> Result<i32, Error> {
+    self.ensure_live("readInt24Signed")?;
+    self
+      .read_u24("readInt24Signed", false)
+      .map(|v| ((v << 8) as i32) >> 8)
+  }
+
+  #[napi(js_name = "readInt24SignedLE")]
+  pub fn read_int24_signed_le(&mut self) -> Result<i32, Error> {
+    self.ensure_live("readInt24SignedLE")?;
+    self
+      .read_u24("readInt24SignedLE", true)
+      .map(|v| ((v << 8) as i32) >> 8)
+  }
+
+  #[napi]
+  pub fn read_int24_unsigned(&mut self) -> Result<u32, Error> {
+    self.ensure_live("readInt24Unsigned")?;
+    self.read_u24("readInt24Unsigned", false)
+  }
+
+  #[napi(js_name = "readInt24UnsignedLE")]
+  pub fn read_int24_unsigned_le(&mut self) -> Result<u32, Error> {
+    self.ensure_live("readInt24UnsignedLE")?;
+    self.read_u24("readInt24UnsignedLE", true)
+  }
+
+  #[napi]
+  pub fn read_int(&mut self) -> Result<i32, Error> {
+    self.ensure_live("readInt")?;
+    if self.readable_bytes() < 4 {
+      return Err(self.underflow("readInt", 4));
+    }
+    self.r_pos += 4;
+    Ok(i32::from_be_bytes(self.buf[self.r_pos - 4..self.r_pos].try_into().unwrap()))
+  }
+
+  #[napi]
+  pub fn read_unsigned_int(&mut self) -> Result<u32, Error> {
+    self.ensure_live("readUnsignedInt")?;
+    if self.readable_bytes() < 4 {
+      return Err(self.underflow("readUnsignedInt", 4));
+    }
+    self.r_pos += 4;
+    Ok(u32::from_be_bytes(self.buf[self.r_pos - 4..self.r_pos].try_into().unwrap()))
+  }
+
+  /// Same as `readUnsignedInt` but as a BigInt, for decoders that handle every wide integer
+  /// the way they handle the 64-bit ones
+  #[napi(js_name = "readUnsignedIntBigInt")]
+  pub fn read_unsigned_int_bigint(&mut self) -> Result<BigInt, Error> {
+    self.ensure_live("readUnsignedIntBigInt")?;
+    if self.readable_bytes() < 4 {
+      return Err(self.underflow("readUnsignedIntBigInt", 4));
+    }
+    self.read_unsigned_int().map(|val| BigInt::from(val as u64))
+  }
+
+  #[napi(js_name = "readLongLE")]
+  pub fn read_long_le(&mut self) -> Result<BigInt, Error> {
+    self.ensure_live("readLongLE")?;
+    if self.readable_bytes() < 8 {
+      return Err(self.underflow("readLongLE", 8));
+    }
+    self.r_pos += 8;
+    Ok(BigInt::from(i64::from_le_bytes(
+      self.buf[self.r_pos - 8..self.r_pos].try_into().unwrap(),
+    )))
+  }
+
+  #[napi]
+  pub fn read_unsigned_long(&mut self) -> Result<BigInt, Error> {
+    self.ensure_live("readUnsignedLong")?;
+    if self.readable_bytes() < 8 {
+      return Err(self.underflow("readUnsignedLong", 8));
+    }
+    self.r_pos += 8;
+    Ok(BigInt::from(u64::from_be_bytes(
+      self.buf[self.r_pos - 8..self.r_pos].try_into().unwrap(),
+    )))
+  }
+
+  #[napi(js_name = "readUnsignedLongLE")]
+  pub fn read_unsigned_long_le(&mut self) -> Result<BigInt, Error> {
+    self.ensure_live("readUnsignedLongLE")?;
+    if self.readable_bytes() < 8 {
+      return Err(self.underflow("readUnsignedLongLE", 8));
+    }
+    self.r_pos += 8;
+    Ok(BigInt::from(u64::from_le_bytes(
+      self.buf[self.r_pos - 8..self.r_pos].try_into().unwrap(),
+    )))
+  }
+
+  /// Decodes the float at the absolute `index` in the default order, callers check the bounds
+  fn read_f32_with_order(&self, index: usize) -> f32 {
+    let bytes = self.buf[index..index + 4].try_into().unwrap();
+    match self.default_order {
+      ByteOrder::BigEndian => f32::from_be_bytes(bytes),
+      ByteOrder::LittleEndian => f32::from_le_bytes(bytes),
+    }
+  }
+
+  /// Decodes the double at the absolute `index` in the default order, callers check the bounds
+  fn read_f64_with_order(&self, index: usize) -> f64 {
+    let bytes = self.buf[index..index + 8].try_into().unwrap();
+    match self.default_order {
+      ByteOrder::BigEndian => f64::from_be_bytes(bytes),
+      ByteOrder::LittleEndian => f64::from_le_bytes(bytes),
+    }
+  }
+
+  /// Errors if `width` bytes at the absolute `index` aren't all written,
+  /// `method` is used in the error message
+  fn check_range(&self, index: u32, width: usize, method: &str) -> Result<usize, Error> {
+    self.ensure_live(method)?;
+    let start = index as usize;
+    if start + width > self.w_pos {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot {}, given range {}..{} exceeds writerIndex {}",
+          method,
+          start,
+          start + width,
+          self.w_pos
+        ),
+      ));
+    }
+    Ok(start)
+  }
+
+  /// Reads a float in the default order, big-endian unless changed
+  #[napi]
+  pub fn read_float(&mut self) -> Result<f64, Error> {
+    self.ensure_live("readFloat")?;
+    if self.readable_bytes() < 4 {
+      return Err(self.underflow("readFloat", 4));
+    }
+    self.r_pos += 4;
+    Ok(self.read_f32_with_order(self.r_pos - 4) as f64)
+  }
+
+  /// Reads a double in the default order, big-endian unless changed
+  #[napi]
+  pub fn read_double(&mut self) -> Result<f64, Error> {
+    self.ensure_live("readDouble")?;
+    if self.readable_bytes() < 8 {
+      return Err(self.underflow("readDouble", 8));
+    }
+    self.r_pos += 8;
+    Ok(self.read_f64_with_order(self.r_pos - 8))
+  }
+
+  /// Returns the float at the absolute `index` in the default order without moving the indices
+  #[napi]
+  pub fn get_float(&self, index: u32) -> Result<f64, Error> {
+    let start = self.check_range(index, 4, "getFloat")?;
+    Ok(self.read_f32_with_order(start) as f64)
+  }
+
+  /// Returns the double at the absolute `index` in the default order without moving the indices
+  #[napi]
+  pub fn get_double(&self, index: u32) -> Result<f64, Error> {
+    let start = self.check_range(index, 8, "getDouble")?;
+    Ok(self.read_f64_with_order(start))
+  }
+
+  /// Checks the big-endian int at the absolute `headerIndex` equals the written length, to catch
+  /// truncated or corrupt frames that carry their total length up front
+  #[napi]
+  pub fn validate_total_length(&self, header_index: u32) -> Result<bool, Error> {
+    let start = self.check_range(header_index, 4, "validateTotalLength")?;
+    let length = u32::from_be_bytes(self.buf[start..start + 4].try_into().unwrap());
+    Ok(length as usize == self.w_pos)
+  }
+
+  /// Reads a big-endian IEEE-754 half-precision (16-bit) float
+  #[napi]
+  pub fn read_half(&mut self) -> Result<f64, Error> {
+    self.ensure_live("readHalf")?;
+    if self.readable_bytes() < 2 {
+      return Err(self.underflow("readHalf", 2));
+    }
+    self.r_pos += 2;
+    let half = u16::from_be_bytes(self.buf[self.r_pos - 2..self.r_pos].try_into().unwrap());
+    Ok(half_to_f32(half) as f64)
+  }
+
+  #[napi]
+  pub fn read_long(&mut self) -> Result<BigInt, Error> {
+    self.ensure_live("readLong")?;
+    if self.readable_bytes() < 8 {
+      return Err(self.underflow("readLong", 8));
+    }
+    self.r_pos += 8;
+    Ok(BigInt::from(i64::from_be_bytes(
+      self.buf[self.r_pos - 8..self.r_pos].try_into().unwrap(),
+    )))
+  }
+
+  /// Like `readByte` but returns `default` without advancing when there isn't a byte left,
+  /// for best-effort parsers
+  #[napi]
+  pub fn read_byte_or(&mut self, default: i32) -> Result<i32, Error> {
+    self.ensure_live("readByteOr")?;
+    Ok(self.read_byte().unwrap_or(default))
+  }
+
+  /// Like `readShort` but returns `default` without advancing when there aren't 2 bytes left
+  #[napi]
+  pub fn read_short_or(&mut self, default: i32) -> Result<i32, Error> {
+    self.ensure_live("readShortOr")?;
+    Ok(self.read_short().unwrap_or(default))
+  }
+
+  /// Like `readInt` but returns `default` without advancing when there aren't 4 bytes left
+  #[napi]
+  pub fn read_int_or(&mut self, default: i32) -> Result<i32, Error> {
+    self.ensure_live("readIntOr")?;
+    Ok(self.read_int().unwrap_or(default))
+  }
+
+  /// Like `readLong` but returns `default` without advancing when there aren't 8 bytes left
+  #[napi]
+  pub fn read_long_or(&mut self, default: BigInt) -> Result<BigInt, Error> {
+    self.ensure_live("readLongOr")?;
+    Ok(self.read_long().unwrap_or(default))
+  }
+
+  /// Reads a byte array prefixed by a big-endian length field,
+  /// the width of the length field (1, 2 or 4 bytes) is chosen by the caller
+  #[napi]
+  pub fn read_sized_byte_array(&mut self, length_bytes: u32) -> Result<Buffer, Error> {
+    self.ensure_live("readSizedByteArray")?;
+    if !matches!(length_bytes, 1 | 2 | 4) {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot readSizedByteArray, given lengthBytes {} is not one of 1, 2 or 4",
+          length_bytes
+        ),
+      ));
+    }
+    if self.readable_bytes() < length_bytes as usize {
+      return Err(self.underflow("readSizedByteArray", length_bytes as usize));
+    }
+    let prefix = &self.buf[self.r_pos..self.r_pos + length_bytes as usize];
+    let length = match length_bytes {
+      1 => prefix[0] as usize,
+      2 => u16::from_be_bytes(prefix.try_into().unwrap()) as usize,
+      _ => u32::from_be_bytes(prefix.try_into().unwrap()) as usize,
+    };
+    let total = length_bytes as usize + length;
+    if self.readable_bytes() < total {
+      return Err(self.underflow("readSizedByteArray", total));
+    }
+    self.r_pos += total;
+    Ok(Buffer::from(&self.buf[self.r_pos - length..self.r_pos]))
+  }
+
+  /// Writes a byte array prefixed by a big-endian length field,
+  /// the width of the length field (1, 2 or 4 bytes) is chosen by the caller
+  #[napi]
+  pub fn write_sized_byte_array(&mut self, length_bytes: u32, buf: Buffer) -> Result<(), Error> {
+    self.ensure_writable("writeSizedByteArray")?;
+    let length = buf.len();
+    let max = match length_bytes {
+      1 => u8::MAX as usize,
+      2 => u16::MAX as usize,
+      4 => u32::MAX as usize,
+      _ => {
+        return Err(Error::new(
+          ErrorCode::InvalidArg,
+          format!(
+            "cannot writeSizedByteArray, given lengthBytes {} is not one of 1, 2 or 4",
+            length_bytes
+          ),
+        ))
+      }
+    };
+    if length > max {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot writeSizedByteArray, given buffer length {} does not fit in {} bytes",
+          length, length_bytes
+        ),
+      ));
+    }
+    match length_bytes {
+      1 => self.write_bytes(&[length as u8])?,
+      2 => self.write_bytes(&(length as u16).to_be_bytes())?,
+      _ => self.write_bytes(&(length as u32).to_be_bytes())?,
+    }
+    self.write_bytes(&buf)
+  }
+
+  /// Decodes the VarInt at the reader index without advancing, returning the value
+  /// and its size, or `None` if the readable bytes end before the VarInt does
+  fn peek_var_int_sized(&self, method: &str) -> Result<Option<(i32, usize)>, Error> {
+    self.peek_var_int_capped(method, 32)
+  }
+
+  fn peek_var_int_capped(
+    &self,
+    method: &str,
+    max_bits: u32,
+  ) -> Result<Option<(i32, usize)>, Error> {
+    let max_bytes = max_bits.div_ceil(7) as usize;
+    let mut value: u32 = 0;
+    for i in 0..max_bytes {
+      if self.r_pos + i >= self.w_pos {
+        return Ok(None);
+      }
+      let byte = self.buf[self.r_pos + i];
+      value |= ((byte & 0x7F) as u32) << (7 * i);
+      if byte & 0x80 == 0 {
+        if max_bits < 32 && value >> max_bits != 0 {
+          return Err(Error::new(
+            ErrorCode::Overflow,
+            format!("cannot {}, VarInt is wider than {} bits", method, max_bits),
+          ));
+        }
+        return Ok(Some((value as i32, i + 1)));
+      }
+    }
+    Err(Error::new(
+      ErrorCode::Overflow,
+      format!("cannot {}, VarInt is longer than {} bytes", method, max_bytes),
+    ))
+  }
+
+  /// Reads a VarInt, 7 bits per byte, least significant group first (at most 5 bytes)
+  ///
+  /// `maxBits` (1 to 32, default 32) caps the width for protocols with restricted VarInts, e.g. 21
+  /// bits allows at most 3 bytes, longer encodings are rejected
+  #[napi]
+  pub fn read_var_int(&mut self, max_bits: Option<u32>) -> Result<i32, Error> {
+    self.ensure_live("readVarInt")?;
+    let max_bits = max_bits.unwrap_or(32);
+    if !(1..=32).contains(&max_bits) {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!("cannot readVarInt, given maxBits {} is not between 1 and 32", max_bits),
+      ));
+    }
+    match self.peek_var_int_capped("readVarInt", max_bits)? {
+      Some((value, size)) => {
+        self.r_pos += size;
+        Ok(value)
+      }
+      None => Err(self.underflow("readVarInt", self.readable_bytes() + 1)),
+    }
+  }
+
+  /// Returns how many bytes (1 to 5) the VarInt at the reader index takes, without consuming it
+  #[napi]
+  pub fn read_var_int_size(&self) -> Result<u32, Error> {
+    self.ensure_live("readVarIntSize")?;
+    match self.peek_var_int_sized("readVarIntSize")? {
+      Some((_, size)) => Ok(size as u32),
+      None => Err(self.underflow("readVarIntSize", self.readable_bytes() + 1)),
+    }
+  }
+
+  /// Decodes the VarInt at the reader index without consuming it, e.g. to sniff a packet id,
+  /// or returns null if it hasn't fully arrived yet
+  #[napi]
+  pub fn peek_var_int(&self) -> Result<Option<i32>, Error> {
+    self.ensure_live("peekVarInt")?;
+    self
+      .peek_var_int_sized("peekVarInt")
+      .map(|peeked| peeked.map(|(value, _)| value))
+  }
+
+  /// Reads a value written by `writeVarIntDelta`, adding the decoded delta to the previous one
+  /// (0 at first, after `clear` or after moving the reader index)
+  #[napi]
+  pub fn read_var_int_delta(&mut self) -> Result<i32, Error> {
+    self.ensure_live("readVarIntDelta")?;
+    let zigzag = self.read_var_int(None)? as u32;
+    let delta = (zigzag >> 1) as i32 ^ -((zigzag & 1) as i32);
+    self.read_delta = self.read_delta.wrapping_add(delta);
+    Ok(self.read_delta)
+  }
+
+  /// Reads a value written by `writeVarMedium`, rejecting VarInts wider than 24 bits
+  #[napi]
+  pub fn read_var_medium(&mut self) -> Result<u32, Error> {
+    self.ensure_live("readVarMedium")?;
+    match self.peek_var_int_capped("readVarMedium", 24)? {
+      Some((value, size)) => {
+        self.r_pos += size;
+        Ok(value as u32)
+      }
+      None => Err(self.underflow("readVarMedium", self.readable_bytes() + 1)),
+    }
+  }
+
+  /// Reads a record written by `writeTlv`, leaving the reader index untouched if it's incomplete
+  #[napi]
+  pub fn read_tlv(&mut self) -> Result<Tlv, Error> {
+    self.ensure_live("readTlv")?;
+    let start = self.r_pos;
+    let header = self
+      .read_var_int(None)
+      .and_then(|tag| Ok((tag as u32, self.read_var_int(None)? as u32 as usize)));
+    let (tag, length) = match header {
+      Ok(header) => header,
+      Err(e) => {
+        self.r_pos = start;
+        return Err(e);
+      }
+    };
+    if self.readable_bytes() < length {
+      let needed = self.r_pos - start + length;
+      self.r_pos = start;
+      return Err(self.underflow("readTlv", needed));
+    }
+    self.r_pos += length;
+    Ok(Tlv {
+      tag,
+      value: Buffer::from(&self.buf[self.r_pos - length..self.r_pos]),
+    })
+  }
+
+  /// Reads a VarLong, the 64-bit VarInt (at most 10 bytes)
+  #[napi]
+  pub fn read_var_long(&mut self) -> Result<BigInt, Error> {
+    self.ensure_live("readVarLong")?;
+    let mut value: u64 = 0;
+    for i in 0..10 {
+      if self.r_pos + i >= self.w_pos {
+        return Err(self.underflow("readVarLong", i + 1));
+      }
+      let byte = self.buf[self.r_pos + i];
+      value |= ((byte & 0x7F) as u64) << (7 * i);
+      if byte & 0x80 == 0 {
+        self.r_pos += i + 1;
+        return Ok(BigInt::from(value as i64));
+      }
+    }
+    Err(Error::new(
+      ErrorCode::Overflow,
+      "cannot readVarLong, VarLong is longer than 10 bytes".to_string(),
+    ))
+  }
+
+  /// Reads a UTF-8 string prefixed by its length in bytes, VarInt prefixed by default
+  #[napi]
+  pub fn read_string(&mut self, encoding: Option<StringEncoding>) -> Result<String, Error> {
+    self.ensure_live("readString")?;
+    let start = self.r_pos;
+    let length = match encoding.unwrap_or(StringEncoding::VarInt) {
+      StringEncoding::VarInt => self.read_var_int(None).map(|v| v as u32 as usize),
+      StringEncoding::UnsignedShort => self.read_unsigned_short().map(|v| v as usize),
+      StringEncoding::UnsignedInt => {
+        if self.readable_bytes() < 4 {
+          Err(self.underflow("readString", 4))
+        } else {
+          self.r_pos += 4;
+          Ok(u32::from_be_bytes(self.buf[self.r_pos - 4..self.r_pos].try_into().unwrap()) as usize)
+        }
+      }
+    }?;
+    if self.readable_bytes() < length {
+      let needed = self.r_pos - start + length;
+      self.r_pos = start;
+      return Err(self.underflow("readString", needed));
+    }
+    match std::str::from_utf8(&self.buf[self.r_pos..self.r_pos + length]) {
+      Ok(val) => {
+        self.r_pos += length;
+        Ok(val.to_string())
+      }
+      Err(e) => {
+        self.r_pos = start;
+        Err(Error::new(
+          ErrorCode::InvalidUtf8,
+          format!("cannot readString, invalid UTF-8: {}", e),
+        ))
+      }
+    }
+  }
+
+  /// Reads a VarInt prefixed UTF-8 string, erroring without advancing if it takes more than
+  /// `maxBytes` bytes or has more than `maxChars` code points (e.g. chat length limits)
+  #[napi]
+  pub fn read_string_limited(&mut self, max_bytes: u32, max_chars: u32) -> Result<String, Error> {
+    self.ensure_live("readStringLimited")?;
+    let start = self.r_pos;
+    let length = self.read_var_int(None)? as u32;
+    self.r_pos = start;
+    if length > max_bytes {
+      return Err(Error::new(
+        ErrorCode::Overflow,
+        format!(
+          "cannot readStringLimited, string length {} is greater than maxBytes {}",
+          length, max_bytes
+        ),
+      ));
+    }
+    let val = self.read_string(None)?;
+    let chars = val.chars().count();
+    if chars > max_chars as usize {
+      self.r_pos = start;
+      return Err(Error::new(
+        ErrorCode::Overflow,
+        format!(
+          "cannot readStringLimited, string has {} characters, more than maxChars {}",
+          chars, max_chars
+        ),
+      ));
+    }
+    Ok(val)
+  }
+
+  /// Reads a Date stored as a big-endian long of milliseconds since the epoch
+  #[napi]
+  pub fn read_date(&mut self, env: Env) -> Result<Date, Error> {
+    self.ensure_live("readDate")?;
+    if self.readable_bytes() < 8 {
+      return Err(self.underflow("readDate", 8));
+    }
+    self.r_pos += 8;
+    let millis = i64::from_be_bytes(self.buf[self.r_pos - 8..self.r_pos].try_into().unwrap());
+    env.create_date(millis as f64).map_err(from_napi)
+  }
+
+  /// Errors if this buffer is read-only, `method` is used in the message
+  fn ensure_writable(&self, method: &str) -> Result<(), Error> {
+    self.ensure_live(method)?;
+    if self.read_only {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        format!("cannot {}, buffer is read-only", method),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Reads `count` big-endian ints in one call
+  #[napi]
+  pub fn read_int_array(&mut self, count: u32) -> Result<Int32Array, Error> {
+    self.ensure_live("readIntArray")?;
+    let length = count as usize * 4;
+    if self.readable_bytes() < length {
+      return Err(self.underflow("readIntArray", length));
+    }
+    let vals = self.buf[self.r_pos..self.r_pos + length]
+      .chunks_exact(4)
+      .map(|c| i32::from_be_bytes(c.try_into().unwrap()))
+      .collect();
+    self.r_pos += length;
+    Ok(Int32Array::new(vals))
+  }
+
+  /// Reads `count` floats in the default order in one call
+  #[napi]
+  pub fn read_float_array(&mut self, count: u32) -> Result<Float32Array, Error> {
+    self.ensure_live("readFloatArray")?;
+    let length = count as usize * 4;
+    if self.readable_bytes() < length {
+      return Err(self.underflow("readFloatArray", length));
+    }
+    let vals = (0..count as usize)
+      .map(|i| self.read_f32_with_order(self.r_pos + i * 4))
+      .collect();
+    self.r_pos += length;
+    Ok(Float32Array::new(vals))
+  }
+
+  /// Reads `count` doubles in the default order in one call
+  #[napi]
+  pub fn read_double_array(&mut self, count: u32) -> Result<Float64Array, Error> {
+    self.ensure_live("readDoubleArray")?;
+    let length = count as usize * 8;
+    if self.readable_bytes() < length {
+      return Err(self.underflow("readDoubleArray", length));
+    }
+    let vals = (0..count as usize)
+      .map(|i| self.read_f64_with_order(self.r_pos + i * 8))
+      .collect();
+    self.r_pos += length;
+    Ok(Float64Array::new(vals))
+  }
+
+  /// Reads a VarInt enum discriminant, erroring if it isn't less than `max`
+  #[napi]
+  pub fn read_enum(&mut self, max: u32) -> Result<u32, Error> {
+    self.ensure_live("readEnum")?;
+    let start = self.r_pos;
+    let val = self.read_var_int(None)? as u32;
+    if val >= max {
+      self.r_pos = start;
+      return Err(Error::new(
+        ErrorCode::Overflow,
+        format!("cannot readEnum, value {} is not less than max {}", val, max),
+      ));
+    }
+    Ok(val)
+  }
+
+  /// Reads a VarInt length prefixed frame into its own buffer,
+  /// or returns null without advancing if the whole frame hasn't arrived yet.
+  /// A length over `maxFrameLength` errors straight away instead of waiting for the bytes
+  #[napi]
+  pub fn read_var_int_prefixed_buf(
+    &mut self,
+    max_frame_length: Option<u32>,
+  ) -> Result<Option<ByteBuf>, Error> {
+    self.ensure_live("readVarIntPrefixedBuf")?;
+    self.read_frame("readVarIntPrefixedBuf", max_frame_length)
+  }
+
+  /// Shared frame reader, `method` names the caller in errors
+  fn read_frame(
+    &mut self,
+    method: &str,
+    max_frame_length: Option<u32>,
+  ) -> Result<Option<ByteBuf>, Error> {
+    let (length, size) = match self.peek_var_int_sized(method)? {
+      Some(res) => res,
+      None => return Ok(None),
+    };
+    if length < 0 {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        format!("cannot {}, length {} is negative", method, length),
+      ));
+    }
+    if let Some(max) = max_frame_length.filter(|&max| length as u32 > max) {
+      return Err(Error::new(
+        ErrorCode::Overflow,
+        format!(
+          "cannot {}, length {} is greater than maxFrameLength {}",
+          method, length, max
+        ),
+      ));
+    }
+    if self.readable_bytes() < size + length as usize {
+      return Ok(None);
+    }
+    self.r_pos += size + length as usize;
+    Ok(Some(ByteBuf::from_vec(
+      self.buf[self.r_pos - length as usize..self.r_pos].to_vec(),
+    )))
+  }
+
+  /// Calls `callback` with each complete VarInt length prefixed frame in the readable bytes,
+  /// stopping with the reader index at the start of the first incomplete frame.
+  /// The reader index moves past a frame before its callback runs, which gets its own copy of
+  /// the frame and can't call back into this buffer. Returns the frame count
+  #[napi(ts_args_type = "callback: (frame: ByteBuf) => void, maxFrameLength?: number | undefined | null")]
+  pub fn decode_frames(
+    &mut self,
+    env: Env,
+    callback: JsFunction,
+    max_frame_length: Option<u32>,
+  ) -> Result<u32, Error> {
+    self.ensure_live("decodeFrames")?;
+    let mut count = 0;
+    while let Some(frame) = self.read_frame("decodeFrames", max_frame_length)? {
+      let frame = frame.into_instance(env).map_err(from_napi)?;
+      self
+        .guard_callback("decodeFrames", || callback.call(None, &[frame.as_object(env)]))
+        .map_err(|e| rethrow(env, e))?;
+      count += 1;
+    }
+    Ok(count)
+  }
+
+  /// Checks the next `length` bytes are printable ASCII without consuming them,
+  /// the error names the absolute index of the first offending byte
+  #[napi]
+  pub fn debug_assert_ascii(&self, length: u32) -> Result<(), Error> {
+    self.ensure_live("debugAssertAscii")?;
+    if self.readable_bytes() < length as usize {
+      return Err(self.underflow("debugAssertAscii", length as usize));
+    }
+    let bytes = &self.buf[self.r_pos..self.r_pos + length as usize];
+    if let Some(i) = bytes.iter().position(|b| !(0x20..=0x7E).contains(b)) {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        format!(
+          "cannot debugAssertAscii, byte 0x{:02x} at index {} is not printable ASCII",
+          bytes[i],
+          self.r_pos + i
+        ),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Reads UTF-16 code units up to a 0x0000 terminator (consumed, not returned)
+  #[napi]
+  pub fn read_utf16_string(&mut self, little_endian: bool) -> Result<String, Error> {
+    self.ensure_live("readUtf16String")?;
+    let units = self.buf[self.r_pos..self.w_pos]
+      .chunks_exact(2)
+      .map(|c| {
+        if little_endian {
+          u16::from_le_bytes([c[0], c[1]])
+        } else {
+          u16::from_be_bytes([c[0], c[1]])
+        }
+      })
+      .take_while(|&u| u != 0)
+      .collect::<Vec<u16>>();
+    if (units.len() + 1) * 2 > self.readable_bytes() {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        "cannot readUtf16String, no terminator in the readable bytes".to_string(),
+      ));
+    }
+    let val = String::from_utf16(&units).map_err(|_| {
+      Error::new(
+        ErrorCode::GenericFailure,
+        "cannot readUtf16String, invalid UTF-16: lone surrogate".to_string(),
+      )
+    })?;
+    self.r_pos += (units.len() + 1) * 2;
+    Ok(val)
+  }
+
+  /// Reads a VarInt count followed by that many VarLongs, erroring if the count exceeds `max`
+  #[napi]
+  pub fn read_var_long_array(&mut self, max: u32) -> Result<Vec<BigInt>, Error> {
+    self.ensure_live("readVarLongArray")?;
+    let start = self.r_pos;
+    let count = self.read_var_int(None)? as u32;
+    if count > max {
+      self.r_pos = start;
+      return Err(Error::new(
+        ErrorCode::Overflow,
+        format!("cannot readVarLongArray, count {} is greater than max {}", count, max),
+      ));
+    }
+    let mut vals = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+      match self.read_var_long() {
+        Ok(val) => vals.push(val),
+        Err(e) => {
+          self.r_pos = start;
+          return Err(e);
+        }
+      }
+    }
+    Ok(vals)
+  }
+
+  /// Reads a VarInt count followed by that many VarInt prefixed UTF-8 strings,
+  /// erroring if the count exceeds `maxCount`
+  #[napi]
+  pub fn read_string_list(&mut self, max_count: u32) -> Result<Vec<String>, Error> {
+    self.ensure_live("readStringList")?;
+    let start = self.r_pos;
+    let count = self.read_var_int(None)? as u32;
+    if count > max_count {
+      self.r_pos = start;
+      return Err(Error::new(
+        ErrorCode::Overflow,
+        format!(
+          "cannot readStringList, count {} is greater than maxCount {}",
+          count, max_count
+        ),
+      ));
+    }
+    let mut vals = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+      match self.read_string(None) {
+        Ok(val) => vals.push(val),
+        Err(e) => {
+          self.r_pos = start;
+          return Err(e);
+        }
+      }
+    }
+    Ok(vals)
+  }
+
+  /// Reads all the readable bytes, leaving the reader index at the writer index
+  #[napi]
+  pub fn read_remaining(&mut self) -> Result<Buffer, Error> {
+    self.ensure_live("readRemaining")?;
+    let res = Buffer::from(&self.buf[self.r_pos..self.w_pos]);
+    self.r_pos = self.w_pos;
+    Ok(res)
+  }
+
+  /// Reads a single value by type name, one of
+  /// `byte`, `short`, `int`, `long`, `float`, `double`, `varint` or `bool`
+  #[napi(ts_return_type = "number | bigint | boolean")]
+  pub fn read_scalar(&mut self, env: Env, type_name: String) -> Result<JsUnknown, Error> {
+    self.ensure_live("readScalar")?;
+    let val = match type_name.as_str() {
+      "byte" => env.create_int32(self.read_byte()?).map(|v| v.into_unknown()),
+      "short" => env.create_int32(self.read_short()?).map(|v| v.into_unknown()),
+      "int" => env.create_int32(self.read_int()?).map(|v| v.into_unknown()),
+      "long" => {
+        let (val, _) = self.read_long()?.get_i64();
+        env.create_bigint_from_i64(val).and_then(|v| v.into_unknown())
+      }
+      "float" => env.create_double(self.read_float()?).map(|v| v.into_unknown()),
+      "double" => env.create_double(self.read_double()?).map(|v| v.into_unknown()),
+      "varint" => env.create_int32(self.read_var_int(None)?).map(|v| v.into_unknown()),
+      "bool" => env.get_boolean(self.read_boolean()?).map(|v| v.into_unknown()),
+      _ => {
+        return Err(Error::new(
+          ErrorCode::InvalidArg,
+          format!("cannot readScalar, unknown type name {}", type_name),
+        ))
+      }
+    };
+    val.map_err(from_napi)
+  }
+
+  /// Reads the next `length` bytes into a new Uint8Array
+  #[napi]
+  pub fn read_into_uint8array(&mut self, length: u32) -> Result<Uint8Array, Error> {
+    self.ensure_live("readIntoUint8Array")?;
+    if self.readable_bytes() < length as usize {
+      return Err(self.underflow("readIntoUint8Array", length as usize));
+    }
+    self.r_pos += length as usize;
+    Ok(Uint8Array::new(
+      self.buf[self.r_pos - length as usize..self.r_pos].to_vec(),
+    ))
+  }
+
+  /// Reads the next `length` bytes as a lowercase hex string,
+  /// handy for logging a single field's raw bytes
+  #[napi]
+  pub fn read_hex(&mut self, length: u32) -> Result<String, Error> {
+    self.ensure_live("readHex")?;
+    if self.readable_bytes() < length as usize {
+      return Err(self.underflow("readHex", length as usize));
+    }
+    let hex = self.buf[self.r_pos..self.r_pos + length as usize]
+      .iter()
+      .map(|b| format!("{:02x}", b))
+      .collect::<String>();
+    self.r_pos += length as usize;
+    Ok(hex)
+  }
+
+  /// Reads `length` bytes of packed BCD, two decimal digits per byte high nibble first.
+  /// A trailing 0xF pad nibble (odd digit count) is dropped
+  #[napi]
+  pub fn read_bcd(&mut self, length: u32) -> Result<String, Error> {
+    self.ensure_live("readBcd")?;
+    let length = length as usize;
+    if self.readable_bytes() < length {
+      return Err(self.underflow("readBcd", length));
+    }
+    let mut digits = String::with_capacity(length * 2);
+    for (i, &byte) in self.buf[self.r_pos..self.r_pos + length].iter().enumerate() {
+      for (j, nibble) in [byte >> 4, byte & 0x0F].into_iter().enumerate() {
+        match nibble {
+          0..=9 => digits.push((b'0' + nibble) as char),
+          0x0F if i == length - 1 && j == 1 => {}
+          _ => {
+            return Err(Error::new(
+              ErrorCode::GenericFailure,
+              format!(
+                "cannot readBcd, nibble {:#x} at index {} is not a digit",
+                nibble,
+                self.r_pos + i
+              ),
+            ));
+          }
+        }
+      }
+    }
+    self.r_pos += length;
+    Ok(digits)
+  }
+
+  /// Checks a bit count is within 1..=max, `method` is used in the error message
+  fn check_bit_count(count: u32, max: u32, method: &str) -> Result<(), Error> {
+    if !(1..=max).contains(&count) {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!("cannot {}, given count {} is not within 1..={}", method, count, max),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Reads `count` (1 to `max_count`) bits starting at the bit offset, a fresh byte is consumed
+  /// once the current one is used up. `le_variant` picks the offset and the fixed LSB-first
+  /// order of `readBitsLE` over those of `readBits`. `method` names the caller in errors
+  fn read_bits_ordered(
+    &mut self,
+    method: &str,
+    count: u32,
+    max_count: u32,
+    le_variant: bool,
+  ) -> Result<u64, Error> {
+    Self::check_bit_count(count, max_count, method)?;
+    let lsb_first = le_variant || self.bits_lsb_first;
+    let mut offset = if le_variant { self.read_bit_le } else { self.read_bit };
+    offset = offset.filter(|o| o.index + 1 == self.r_pos && o.used < 8);
+    let available = offset.map_or(0, |o| 8 - o.used) as usize + self.readable_bytes() * 8;
+    if available < count as usize {
+      return Err(Error::new(
+        ErrorCode::Underflow,
+        format!(
+          "cannot {}, readable bits {} are less than {} at readerIndex {}",
+          method, available, count, self.r_pos
+        ),
+      ));
+    }
+    let mut val: u64 = 0;
+    for i in 0..count {
+      let o = match offset {
+        Some(o) if o.used < 8 => o,
+        _ => {
+          self.r_pos += 1;
+          BitOffset {
+            index: self.r_pos - 1,
+            used: 0,
+          }
+        }
+      };
+      let byte = self.buf[o.index] as u64;
+      if lsb_first {
+        val |= ((byte >> o.used) & 1) << i;
+      } else {
+        val = (val << 1) | ((byte >> (7 - o.used)) & 1);
+      }
+      offset = Some(BitOffset {
+        index: o.index,
+        used: o.used + 1,
+      });
+    }
+    if le_variant {
+      self.read_bit_le = offset;
+    } else {
+      self.read_bit = offset;
+    }
+    Ok(val)
+  }
+
+  /// Reads `count` (1 to 32) bits, most significant bit first within each byte unless
+  /// `setBitOrder(false)` was called. Byte reads after a bit read start at the next whole byte
+  #[napi]
+  pub fn read_bits(&mut self, count: u32) -> Result<u32, Error> {
+    self.ensure_live("readBits")?;
+    self
+      .read_bits_ordered("readBits", count, 32, false)
+      .map(|val| val as u32)
+  }
+
+  /// Reads `count` (1 to 32) bits packed least significant bit first, like deflate.
+  /// Keeps its own bit offset, separate from `readBits` even after `setBitOrder(false)`,
+  /// so switching between the two mid-byte moves on to the next whole byte
+  #[napi(js_name = "readBitsLE")]
+  pub fn read_bits_le(&mut self, count: u32) -> Result<u32, Error> {
+    self.ensure_live("readBitsLE")?;
+    self
+      .read_bits_ordered("readBitsLE", count, 32, true)
+      .map(|val| val as u32)
+  }
+
+  /// Like `readBits` but for fields of up to 64 bits, returned as a BigInt.
+  /// Shares the bit offset and bit order of `readBits`
+  #[napi]
+  pub fn read_bits_wide(&mut self, count: u32) -> Result<BigInt, Error> {
+    self.ensure_live("readBitsWide")?;
+    self
+      .read_bits_ordered("readBitsWide", count, 64, false)
+      .map(BigInt::from)
+  }
+
+  /// Returns the unsigned byte `offset` bytes past the reader index without advancing
+  #[napi]
+  pub fn peek_at(&self, offset: u32) -> Result<i32, Error> {
+    self.ensure_live("peekAt")?;
+    let offset = offset as usize;
+    if self.readable_bytes() <= offset {
+      return Err(self.underflow("peekAt", offset + 1));
+    }
+    Ok(self.buf[self.r_pos + offset] as i32)
+  }
+
+  /// Returns the absolute index of the first readable byte equal to `value` (truncated to a byte
+  /// like `writeByte`), or -1
+  #[napi]
+  pub fn index_of(&self, value: i32) -> Result<i32, Error> {
+    self.ensure_live("indexOf")?;
+    let readable = &self.buf[self.r_pos..self.w_pos];
+    match readable.iter().position(|&b| b == value as u8) {
+      Some(i) => to_i32(self.r_pos + i, "index"),
+      None => Ok(-1),
+    }
+  }
+
+  /// Returns the absolute index of the last readable byte equal to `value`, or -1 (for trailers)
+  #[napi]
+  pub fn last_index_of(&self, value: i32) -> Result<i32, Error> {
+    self.ensure_live("lastIndexOf")?;
+    let readable = &self.buf[self.r_pos..self.w_pos];
+    match readable.iter().rposition(|&b| b == value as u8) {
+      Some(i) => to_i32(self.r_pos + i, "index"),
+      None => Ok(-1),
+    }
+  }
+
+  /// Counts the readable bytes from the reader index on for which `predicate` (called with
+  /// the unsigned byte) returns truthy, stopping at the first miss. Doesn't advance
+  #[napi(ts_args_type = "predicate: (byte: number) => boolean")]
+  pub fn count_while(&self, env: Env, predicate: JsFunction) -> Result<u32, Error> {
+    self.ensure_live("countWhile")?;
+    let mut count = 0;
+    while self.r_pos + count < self.w_pos {
+      let byte = self.buf[self.r_pos + count] as u32;
+      let matches = self
+        .guard_callback("countWhile", || {
+          env
+            .create_uint32(byte)
+            .and_then(|byte| predicate.call(None, &[byte]))
+            .and_then(|val| val.coerce_to_bool())
+            .and_then(|val| val.get_value())
+        })
+        .map_err(|e| rethrow(env, e))?;
+      if !matches {
+        break;
+      }
+      count += 1;
+    }
+    Ok(count as u32)
+  }
+
+  /// Advances the reader index past the leading bytes matching `predicate` (see `countWhile`),
+  /// returns how many were skipped
+  #[napi(ts_args_type = "predicate: (byte: number) => boolean")]
+  pub fn skip_while(&mut self, env: Env, predicate: JsFunction) -> Result<u32, Error> {
+    self.ensure_live("skipWhile")?;
+    let count = self.count_while(env, predicate)? as usize;
+    self.r_pos += count;
+    Ok(count as u32)
+  }
+
+  /// Reads the next `length` bytes into an owned Buffer, valid after later writes reallocate
+  /// the backing store
+  #[napi]
+  pub fn read_bytes_copy(&mut self, length: u32) -> Result<Buffer, Error> {
+    self.ensure_live("readBytesCopy")?;
+    let length = length as usize;
+    if self.readable_bytes() < length {
+      return Err(self.underflow("readBytesCopy", length));
+    }
+    self.r_pos += length;
+    Ok(Buffer::from(&self.buf[self.r_pos - length..self.r_pos]))
+  }
+
+  /// Reads the next `length` bytes in reverse order, e.g. to flip a little-endian blob
+  #[napi]
+  pub fn read_bytes_reversed(&mut self, length: u32) -> Result<Buffer, Error> {
+    self.ensure_live("readBytesReversed")?;
+    if self.readable_bytes() < length as usize {
+      return Err(self.underflow("readBytesReversed", length as usize));
+    }
+    let mut bytes = self.buf[self.r_pos..self.r_pos + length as usize].to_vec();
+    bytes.reverse();
+    self.r_pos += length as usize;
+    Ok(bytes.into())
+  }
+
+  /// Decodes all the readable bytes as `utf-8`, `ascii` or `latin1`, leaving the reader index
+  /// at the writer index
+  #[napi]
+  pub fn read_string_to_end(&mut self, encoding: String) -> Result<String, Error> {
+    self.ensure_live("readStringToEnd")?;
+    let bytes = &self.buf[self.r_pos..self.w_pos];
+    let val = match encoding.as_str() {
+      "utf-8" | "utf8" => std::str::from_utf8(bytes)
+        .map(|val| val.to_string())
+        .map_err(|e| {
+          Error::new(
+            ErrorCode::InvalidUtf8,
+            format!("cannot readStringToEnd, invalid UTF-8: {}", e),
+          )
+        })?,
+      "ascii" => {
+        if let Some(i) = bytes.iter().position(|b| !b.is_ascii()) {
+          return Err(Error::new(
+            ErrorCode::GenericFailure,
+            format!(
+              "cannot readStringToEnd, byte 0x{:02x} at index {} is not ASCII",
+              bytes[i],
+              self.r_pos + i
+            ),
+          ));
+        }
+        bytes.iter().map(|&b| b as char).collect()
+      }
+      "latin1" => bytes.iter().map(|&b| b as char).collect(),
+      _ => {
+        return Err(Error::new(
+          ErrorCode::InvalidArg,
+          format!("cannot readStringToEnd, unknown encoding {}", encoding),
+        ))
+      }
+    };
+    self.r_pos = self.w_pos;
+    Ok(val)
+  }
+
+  /// Reads one value per type name in `spec` (see `readScalar`) in a single call,
+  /// rolling the reader index back if any of them fails
+  #[napi(ts_return_type = "Array<number | bigint | boolean>")]
+  pub fn read_batch(&mut self, env: Env, spec: Vec<String>) -> Result<Vec<JsUnknown>, Error> {
+    self.ensure_live("readBatch")?;
+    let start = self.r_pos;
+    let mut vals = Vec::with_capacity(spec.len());
+    for type_name in spec {
+      match self.read_scalar(env, type_name) {
+        Ok(val) => vals.push(val),
+        Err(e) => {
+          self.r_pos = start;
+          return Err(e);
+        }
+      }
+    }
+    Ok(vals)
+  }
+
+  /// Reads a record into an object keyed by field name, each field typed like `readScalar`
+  /// plus `string` (VarInt prefixed), rolling the reader index back if any of them fails
+  #[napi(ts_return_type = "Record<string, number | bigint | boolean | string>")]
+  pub fn read_struct(&mut self, env: Env, fields: Vec<StructField>) -> Result<JsObject, Error> {
+    self.ensure_live("readStruct")?;
+    let start = self.r_pos;
+    let res = self.read_struct_fields(env, &fields);
+    if res.is_err() {
+      self.r_pos = start;
+    }
+    res
+  }
+
+  fn read_struct_fields(&mut self, env: Env, fields: &[StructField]) -> Result<JsObject, Error> {
+    let mut obj = env.create_object().map_err(from_napi)?;
+    for field in fields {
+      let val = if field.type_name == "string" {
+        let val = self.read_string(None)?;
+        env.create_string(&val).map_err(from_napi)?.into_unknown()
+      } else {
+        self.read_scalar(env, field.type_name.clone())?
+      };
+      obj.set_named_property(&field.name, val).map_err(from_napi)?;
+    }
+    Ok(obj)
+  }
+
+  /// Reads a count typed by `countType` (`byte`, `short`, `int` or `varint`, unsigned) followed
+  /// by that many records (see `readStruct`), erroring if the count exceeds `maxCount`.
+  /// Rolls the reader index back if anything fails
+  #[napi(ts_return_type = "Array<Record<string, number | bigint | boolean | string>>")]
+  pub fn read_struct_list(
+    &mut self,
+    env: Env,
+    count_type: String,
+    fields: Vec<StructField>,
+    max_count: u32,
+  ) -> Result<Vec<JsObject>, Error> {
+    self.ensure_live("readStructList")?;
+    let start = self.r_pos;
+    let res = self.read_struct_list_items(env, &count_type, &fields, max_count);
+    if res.is_err() {
+      self.r_pos = start;
+    }
+    res
+  }
+
+  fn read_struct_list_items(
+    &mut self,
+    env: Env,
+    count_type: &str,
+    fields: &[StructField],
+    max_count: u32,
+  ) -> Result<Vec<JsObject>, Error> {
+    let count = match count_type {
+      "byte" => self.read_unsigned_byte()?,
+      "short" => self.read_unsigned_short()?,
+      "int" => self.read_int()? as u32,
+      "varint" => self.read_var_int(None)? as u32,
+      _ => {
+        return Err(Error::new(
+          ErrorCode::InvalidArg,
+          format!("cannot readStructList, unknown count type {}", count_type),
+        ))
+      }
+    };
+    if count > max_count {
+      return Err(Error::new(
+        ErrorCode::Overflow,
+        format!(
+          "cannot readStructList, count {} is greater than maxCount {}",
+          count, max_count
+        ),
+      ));
+    }
+    (0..count)
+      .map(|_| self.read_struct_fields(env, fields))
+      .collect()
+  }
+
+  /// Returns a copy of the next `length` readable bytes without advancing, for scanning
+  /// a header in place. The copy stays valid whatever happens to this buffer afterwards
+  #[napi]
+  pub fn peek_window(&self, length: u32) -> Result<Buffer, Error> {
+    self.ensure_live("peekWindow")?;
+    if self.readable_bytes() < length as usize {
+      return Err(self.underflow("peekWindow", length as usize));
+    }
+    Ok(Buffer::from(&self.buf[self.r_pos..self.r_pos + length as usize]))
+  }
+
+  /// Writes data at the writer index, overwriting what was written past it
+  /// and growing the buffer when it runs past the end
+  /// tries to max out performance by using
+  /// direct memory pointers in a unsafe context
+  /// implies minimal copy.
+  pub fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Error> {
+    self.check_max_capacity(buf.len())?;
+    let old_capacity = self.buf.capacity();
+    let old_readable = self.readable_bytes();
+    let len = buf.len();
+    let mark = self.watched().then(|| self.mark_span(len));
+    let start = self.w_pos;
+    self.reserve((start + len).saturating_sub(self.buf.len()));
+    if start > self.buf.len() {
+      self.buf.resize(start, 0);
+    }
+    unsafe {
+      std::ptr::copy_nonoverlapping(buf.as_ptr(), self.buf.as_mut_ptr().add(start), len);
+      self.buf.set_len(self.buf.len().max(start + len));
+    }
+
+    // self.buf.extend_from_slice(buf);
+    self.w_pos += buf.len();
+    self.notify_write(mark, old_capacity, old_readable)
+  }
+
+  fn watched(&self) -> bool {
+    self.grow_callback.is_some() || self.readable_callback.is_some()
+  }
+
+  /// Fires the `onGrow` and `onReadable` callbacks after a write or a writer index move, undoing
+  /// it back to `mark` if one of them throws, so the caller never sees an error for a change
+  /// that landed
+  fn notify_write(
+    &mut self,
+    mark: Option<WriteMark>,
+    old_capacity: usize,
+    old_readable: usize,
+  ) -> Result<(), Error> {
+    let res = self.notify_grow(old_capacity).and_then(|_| self.notify_readable(old_readable));
+    if let (Err(_), Some(mark)) = (&res, mark) {
+      self.reset_to_mark(mark);
+    }
+    res
+  }
+
+  /// Appends all the bytes of a Buffer or a plain Uint8Array
+  #[napi]
+  pub fn append(&mut self, data: Either<Buffer, Uint8Array>) -> Result<(), Error> {
+    self.ensure_writable("append")?;
+    match data {
+      Either::A(buffer) => self.write_bytes(&buffer),
+      Either::B(array) => self.write_bytes(&array),
+    }
+  }
+
+  /// Moves `length` readable bytes out of `source` (advancing its reader index) onto the end
+  /// of this buffer, for relaying between buffers without a JS copy in between
+  #[napi]
+  pub fn append_from(
+    &mut self,
+    mut source: ClassInstance<ByteBuf>,
+    length: u32,
+  ) -> Result<(), Error> {
+    self.ensure_writable("appendFrom")?;
+    if std::ptr::eq(self, &*source) {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        "cannot appendFrom, given source is this buffer".to_string(),
+      ));
+    }
+    let length = length as usize;
+    if source.readable_bytes() < length {
+      return Err(source.underflow("appendFrom", length));
+    }
+    let start = source.r_pos;
+    self.write_bytes(&source.buf[start..start + length])?;
+    source.r_pos += length;
+    Ok(())
+  }
+
+  /// Fires the `onGrow` callback if the capacity went past `old_capacity`
+  fn notify_grow(&self, old_capacity: usize) -> Result<(), Error> {
+    if let Some((env, callback)) = &self.grow_callback {
+      if self.buf.capacity() > old_capacity {
+        let capacities = (old_capacity as u32, self.buf.capacity() as u32);
+        self
+          .guard_callback("onGrow", || {
+            callback.borrow_back(env).and_then(|cb| cb.call(capacities))
+          })
+          .map_err(|e| rethrow(*env, e))?;
+      }
+    }
+    Ok(())
+  }
+
+  #[napi]
+  pub fn write_boolean(&mut self, val: bool) -> Result<(), Error> {
+    self.ensure_writable("writeBoolean")?;
+    self.write_byte(val as i32)
+  }
+
+  /// Writes both a signed / unsigned byte
+  #[napi]
+  pub fn write_byte(&mut self, val: i32) -> Result<(), Error> {
+    self.ensure_writable("writeByte")?;
+    self.write_bytes(&[val as u8])
+  }
+
+  #[napi]
+  pub fn write_short(&mut self, val: i32) -> Result<(), Error> {
+    self.ensure_writable("writeShort")?;
+    self.write_bytes(&(val as i16).to_be_bytes())
+  }
+
+  /// Writes a big-endian signed 24-bit int, erroring instead of truncating outside
+  /// -8388608..=8388607
+  #[napi]
+  pub fn write_medium(&mut self, val: i32) -> Result<(), Error> {
+    self.ensure_writable("writeMedium")?;
+    Self::check_medium("writeMedium", val as i64, -0x80_0000, 0x7F_FFFF)?;
+    self.write_bytes(&[(val >> 16) as u8, (val >> 8) as u8, val as u8])
+  }
+
+  /// Writes a big-endian unsigned 24-bit int, erroring outside 0..=16777215
+  #[napi]
+  pub fn write_unsigned_medium(&mut self, val: u32) -> Result<(), Error> {
+    self.ensure_writable("writeUnsignedMedium")?;
+    Self::check_medium("writeUnsignedMedium", val as i64, 0, 0xFF_FFFF)?;
+    self.write_bytes(&[(val >> 16) as u8, (val >> 8) as u8, val as u8])
+  }
+
+  fn check_medium(method: &str, val: i64, min: i64, max: i64) -> Result<(), Error> {
+    if !(min..=max).contains(&val) {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!("cannot {}, given value {} is not between {} and {}", method, val, min, max),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Writes a big-endian IEEE-754 half-precision (16-bit) float, rounding to nearest
+  #[napi]
+  pub fn write_half(&mut self, val: f64) -> Result<(), Error> {
+    self.ensure_writable("writeHalf")?;
+    self.write_bytes(&f32_to_half(val as f32).to_be_bytes())
+  }
+
+  #[napi]
+  pub fn write_int(&mut self, val: i32) -> Result<(), Error> {
+    self.ensure_writable("writeInt")?;
+    self.write_bytes(&val.to_be_bytes())
+  }
+
+  #[napi]
+  pub fn write_long(&mut self, val: BigInt) -> Result<(), Error> {
+    self.ensure_writable("writeLong")?;
+    let val = bigint_to_i64(&val, "writeLong")?;
+    self.write_bytes(&val.to_be_bytes())
+  }
+
+  #[napi(js_name = "writeLongLE")]
+  pub fn write_long_le(&mut self, val: BigInt) -> Result<(), Error> {
+    self.ensure_writable("writeLongLE")?;
+    let val = bigint_to_i64(&val, "writeLongLE")?;
+    self.write_bytes(&val.to_le_bytes())
+  }
+
+  #[napi]
+  pub fn write_unsigned_long(&mut self, val: BigInt) -> Result<(), Error> {
+    self.ensure_writable("writeUnsignedLong")?;
+    let val = bigint_to_u64(&val, "writeUnsignedLong")?;
+    self.write_bytes(&val.to_be_bytes())
+  }
+
+  #[napi(js_name = "writeUnsignedLongLE")]
+  pub fn write_unsigned_long_le(&mut self, val: BigInt) -> Result<(), Error> {
+    self.ensure_writable("writeUnsignedLongLE")?;
+    let val = bigint_to_u64(&val, "writeUnsignedLongLE")?;
+    self.write_bytes(&val.to_le_bytes())
+  }
+
+  /// Writes a float in the default order, big-endian unless changed
+  #[napi]
+  pub fn write_float(&mut self, val: f64) -> Result<(), Error> {
+    self.ensure_writable("writeFloat")?;
+    let val = val as f32;
+    match self.default_order {
+      ByteOrder::BigEndian => self.write_bytes(&val.to_be_bytes()),
+      ByteOrder::LittleEndian => self.write_bytes(&val.to_le_bytes()),
+    }
+  }
+
+  /// Writes a double in the default order, big-endian unless changed
+  #[napi]
+  pub fn write_double(&mut self, val: f64) -> Result<(), Error> {
+    self.ensure_writable("writeDouble")?;
+    match self.default_order {
+      ByteOrder::BigEndian => self.write_bytes(&val.to_be_bytes()),
+      ByteOrder::LittleEndian => self.write_bytes(&val.to_le_bytes()),
+    }
+  }
+
+  /// Writes a VarInt, negative values always take 5 bytes. Returns the number of bytes written
+  #[napi]
+  pub fn write_var_int(&mut self, val: i32) -> Result<u32, Error> {
+    self.ensure_writable("writeVarInt")?;
+    let (bytes, len) = encode_var_int(val);
+    self.write_bytes(&bytes[..len])?;
+    Ok(len as u32)
+  }
+
+  /// Writes an unsigned 24-bit value (0 to 16777215) as a VarInt instead of `writeMedium`'s
+  /// fixed 3 bytes: up to 127 takes 1 byte, and since each byte carries 7 bits, values of
+  /// 2097152 and up take 4. Returns the number of bytes written
+  #[napi]
+  pub fn write_var_medium(&mut self, val: u32) -> Result<u32, Error> {
+    self.ensure_writable("writeVarMedium")?;
+    Self::check_medium("writeVarMedium", val as i64, 0, 0xFF_FFFF)?;
+    self.write_var_int(val as i32)
+  }
+
+  /// Writes a VarLong, negative values always take 10 bytes. Returns the number of bytes written
+  #[napi]
+  pub fn write_var_long(&mut self, val: BigInt) -> Result<u32, Error> {
+    self.ensure_writable("writeVarLong")?;
+    let mut val = bigint_to_i64(&val, "writeVarLong")? as u64;
+    let mut bytes = [0u8; 10];
+    let mut len = 0;
+    loop {
+      if val & !0x7F == 0 {
+        bytes[len] = val as u8;
+        len += 1;
+        break;
+      }
+      bytes[len] = (val & 0x7F | 0x80) as u8;
+      len += 1;
+      val >>= 7;
+    }
+    self.write_bytes(&bytes[..len])?;
+    Ok(len as u32)
+  }
+
+  /// Writes the zig-zag VarInt of `val` minus the previous value passed here (0 at first, after
+  /// `clear` or after moving the writer index), so sorted sequences take a byte or two per value.
+  /// A write rolled back, by a transaction or a throwing callback, restores the previous value.
+  /// Returns the bytes written
+  #[napi]
+  pub fn write_var_int_delta(&mut self, val: i32) -> Result<u32, Error> {
+    self.ensure_writable("writeVarIntDelta")?;
+    let delta = val.wrapping_sub(self.write_delta);
+    let written = self.write_var_int((delta << 1) ^ (delta >> 31))?;
+    self.write_delta = val;
+    Ok(written)
+  }
+
+  /// Writes a UTF-8 string prefixed by its length in bytes, VarInt prefixed by default
+  #[napi]
+  pub fn write_string(&mut self, val: String, encoding: Option<StringEncoding>) -> Result<(), Error> {
+    self.ensure_writable("writeString")?;
+    let length = val.len();
+    match encoding.unwrap_or(StringEncoding::VarInt) {
+      StringEncoding::VarInt if length <= i32::MAX as usize => {
+        self.write_var_int(length as i32)?;
+      }
+      StringEncoding::UnsignedShort if length <= u16::MAX as usize => {
+        self.write_bytes(&(length as u16).to_be_bytes())?
+      }
+      StringEncoding::UnsignedInt if length <= u32::MAX as usize => {
+        self.write_bytes(&(length as u32).to_be_bytes())?
+      }
+      _ => {
+        return Err(Error::new(
+          ErrorCode::InvalidArg,
+          format!(
+            "cannot writeString, given string length {} does not fit in its length prefix",
+            length
+          ),
+        ))
+      }
+    }
+    self.write_bytes(val.as_bytes())
+  }
+
+  /// Writes a VarInt prefixed UTF-8 string, erroring without writing anything if it takes
+  /// more than `max_bytes` bytes, returns the total bytes written including the prefix
+  #[napi]
+  pub fn write_string_capped(&mut self, val: String, max_bytes: u32) -> Result<u32, Error> {
+    self.ensure_writable("writeStringCapped")?;
+    let length = val.len();
+    if length > max_bytes as usize || length > i32::MAX as usize {
+      return Err(Error::new(
+        ErrorCode::Overflow,
+        format!(
+          "cannot writeStringCapped, string length {} is greater than maxBytes {}",
+          length, max_bytes
+        ),
+      ));
+    }
+    let prefix = self.write_var_int(length as i32)?;
+    self.write_bytes(val.as_bytes())?;
+    Ok(prefix + length as u32)
+  }
+
+  /// Decodes a base64 string and appends the bytes, returns how many were written.
+  /// `urlSafe` switches to the `-_` alphabet, trailing `=` padding is optional either way
+  #[napi]
+  pub fn write_base64(&mut self, s: String, url_safe: Option<bool>) -> Result<u32, Error> {
+    self.ensure_writable("writeBase64")?;
+    let (c62, c63) = if url_safe.unwrap_or(false) {
+      (b'-', b'_')
+    } else {
+      (b'+', b'/')
+    };
+    let trimmed = s.strip_suffix("==").or_else(|| s.strip_suffix('=')).unwrap_or(&s);
+    if trimmed.len() % 4 == 1 || (trimmed.len() != s.len() && !s.len().is_multiple_of(4)) {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!("cannot writeBase64, given string length {} is not valid base64", s.len()),
+      ));
+    }
+    let mut bytes = Vec::with_capacity(trimmed.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    for (i, c) in trimmed.bytes().enumerate() {
+      let sextet = match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        _ if c == c62 => 62,
+        _ if c == c63 => 63,
+        _ => {
+          return Err(Error::new(
+            ErrorCode::InvalidArg,
+            format!(
+              "cannot writeBase64, given string has invalid character {:?} at {}",
+              c as char, i
+            ),
+          ))
+        }
+      };
+      acc = (acc << 6) | sextet as u32;
+      if i % 4 == 3 {
+        bytes.extend_from_slice(&acc.to_be_bytes()[1..]);
+        acc = 0;
+      }
+    }
+    match trimmed.len() % 4 {
+      2 => bytes.push((acc >> 4) as u8),
+      3 => bytes.extend_from_slice(&((acc >> 2) as u16).to_be_bytes()),
+      _ => {}
+    }
+    self.write_bytes(&bytes)?;
+    Ok(bytes.len() as u32)
+  }
+
+  /// Writes `data` prefixed by its length as a VarInt, in one call
+  #[napi]
+  pub fn write_var_int_bytes(&mut self, data: Buffer) -> Result<(), Error> {
+    self.ensure_writable("writeVarIntBytes")?;
+    if data.len() > i32::MAX as usize {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!("cannot writeVarIntBytes, given buffer length {} is too long", data.len()),
+      ));
+    }
+    let (prefix, prefix_len) = encode_var_int(data.len() as i32);
+    self.write_parts(&[&prefix[..prefix_len], &data])
+  }
+
+  /// Writes `parts` back to back, undoing the ones already written if a later one fails
+  /// so a prefix is never left behind without what it announces
+  fn write_parts(&mut self, parts: &[&[u8]]) -> Result<(), Error> {
+    let total = parts.iter().map(|part| part.len()).sum();
+    self.check_max_capacity(total)?;
+    let mark = self.watched().then(|| self.mark_span(total));
+    for part in parts {
+      if let Err(e) = self.write_bytes(part) {
+        if let Some(mark) = mark {
+          self.reset_to_mark(mark);
+        }
+        return Err(e);
+      }
+    }
+    Ok(())
+  }
+
+  /// Writes a tag-length-value record: the tag and the value length as VarInts, then the value
+  #[napi]
+  pub fn write_tlv(&mut self, tag: u32, value: Buffer) -> Result<(), Error> {
+    self.ensure_writable("writeTlv")?;
+    if value.len() > i32::MAX as usize {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!("cannot writeTlv, given value length {} is too long", value.len()),
+      ));
+    }
+    let (tag_bytes, tag_len) = encode_var_int(tag as i32);
+    let (length_bytes, length_len) = encode_var_int(value.len() as i32);
+    self.write_parts(&[&tag_bytes[..tag_len], &length_bytes[..length_len], &value])
+  }
+
+  /// Writes a digit string as packed BCD, two digits per byte high nibble first,
+  /// an odd count is padded with a 0xF nibble
+  #[napi]
+  pub fn write_bcd(&mut self, digits: String) -> Result<(), Error> {
+    self.ensure_writable("writeBcd")?;
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_digit()) {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!("cannot writeBcd, given digits contain non-digit {:?}", c),
+      ));
+    }
+    let packed = digits
+      .as_bytes()
+      .chunks(2)
+      .map(|pair| {
+        let low = pair.get(1).map_or(0x0F, |d| d - b'0');
+        ((pair[0] - b'0') << 4) | low
+      })
+      .collect::<Vec<u8>>();
+    self.write_bytes(&packed)
+  }
+
+  /// Writes a Date as a big-endian long of milliseconds since the epoch
+  #[napi]
+  pub fn write_date(&mut self, date: Date) -> Result<(), Error> {
+    self.ensure_writable("writeDate")?;
+    let millis = date.value_of().map_err(from_napi)?;
+    if millis.is_nan() {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        "cannot writeDate, given date is invalid".to_string(),
+      ));
+    }
+    self.write_bytes(&(millis as i64).to_be_bytes())
+  }
+
+  /// Writes every element as a big-endian int in one call
+  #[napi]
+  pub fn write_int_array(&mut self, data: Int32Array) -> Result<(), Error> {
+    self.ensure_writable("writeIntArray")?;
+    let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_be_bytes()).collect();
+    self.write_bytes(&bytes)
+  }
+
+  /// Writes every element as a float in the default order in one call
+  #[napi]
+  pub fn write_float_array(&mut self, data: Float32Array) -> Result<(), Error> {
+    self.ensure_writable("writeFloatArray")?;
+    let bytes: Vec<u8> = match self.default_order {
+      ByteOrder::BigEndian => data.iter().flat_map(|v| v.to_be_bytes()).collect(),
+      ByteOrder::LittleEndian => data.iter().flat_map(|v| v.to_le_bytes()).collect(),
+    };
+    self.write_bytes(&bytes)
+  }
+
+  /// Writes every element as a double in the default order in one call
+  #[napi]
+  pub fn write_double_array(&mut self, data: Float64Array) -> Result<(), Error> {
+    self.ensure_writable("writeDoubleArray")?;
+    let bytes: Vec<u8> = match self.default_order {
+      ByteOrder::BigEndian => data.iter().flat_map(|v| v.to_be_bytes()).collect(),
+      ByteOrder::LittleEndian => data.iter().flat_map(|v| v.to_le_bytes()).collect(),
+    };
+    self.write_bytes(&bytes)
+  }
+
+  /// Reserves `length` zeroed bytes, advances the writer index past them and
+  /// returns a zeroed Uint8Array for the caller to fill. The array is an owned copy,
+  /// mutations don't propagate back until it's passed to `commitWritableSlice`
+  #[napi]
+  pub fn writable_slice(&mut self, length: u32) -> Result<Uint8Array, Error> {
+    self.ensure_writable("writableSlice")?;
+    self.check_max_capacity(length as usize)?;
+    let old_capacity = self.buf.capacity();
+    let old_readable = self.readable_bytes();
+    let mark = self.watched().then(|| self.mark_span(length as usize));
+    let start = self.w_pos;
+    let end = start + length as usize;
+    self.reserve(end.saturating_sub(self.buf.len()));
+    self.buf.resize(self.buf.len().max(end), 0);
+    self.buf[start..end].fill(0);
+    self.w_pos = end;
+    self.pending_slice = Some((start, length as usize));
+    self.notify_write(mark, old_capacity, old_readable)?;
+    Ok(Uint8Array::new(vec![0; length as usize]))
+  }
+
+  /// Copies a filled `writableSlice` array into the region it reserved
+  #[napi]
+  pub fn commit_writable_slice(&mut self, slice: Uint8Array) -> Result<(), Error> {
+    self.ensure_writable("commitWritableSlice")?;
+    let Some((start, length)) = self.pending_slice else {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        "cannot commitWritableSlice, no writableSlice is pending".to_string(),
+      ));
+    };
+    if slice.len() != length {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot commitWritableSlice, given length {} is not the reserved length {}",
+          slice.len(),
+          length
+        ),
+      ));
+    } else if start + length > self.w_pos {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        "cannot commitWritableSlice, the reserved region was discarded".to_string(),
+      ));
+    }
+    self.buf[start..start + length].copy_from_slice(&slice);
+    self.pending_slice = None;
+    Ok(())
+  }
+
+  /// Writes an enum discriminant as a VarInt, erroring if it isn't less than `max`
+  #[napi]
+  pub fn write_enum(&mut self, val: u32, max: u32) -> Result<(), Error> {
+    self.ensure_writable("writeEnum")?;
+    if val >= max {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!("cannot writeEnum, given value {} is not less than max {}", val, max),
+      ));
+    }
+    self.write_var_int(val as i32).map(|_| ())
+  }
+
+  /// Writes a VarInt, erroring instead if its encoding would take more than `max_bytes`
+  #[napi]
+  pub fn write_var_int_max(&mut self, val: i32, max_bytes: u32) -> Result<(), Error> {
+    self.ensure_writable("writeVarIntMax")?;
+    let len = var_int_len(val);
+    if len > max_bytes as usize {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot writeVarIntMax, given value {} takes {} bytes, more than maxBytes {}",
+          val, len, max_bytes
+        ),
+      ));
+    }
+    self.write_var_int(val).map(|_| ())
+  }
+
+  /// Writes the string as UTF-16 code units followed by a 0x0000 terminator
+  #[napi]
+  pub fn write_utf16_string(&mut self, val: String, little_endian: bool) -> Result<(), Error> {
+    self.ensure_writable("writeUtf16String")?;
+    if val.contains('\0') {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        "cannot writeUtf16String, given string contains a NUL character".to_string(),
+      ));
     }
+    let bytes = val
+      .encode_utf16()
+      .chain(std::iter::once(0))
+      .flat_map(|u| {
+        if little_endian {
+          u.to_le_bytes()
+        } else {
+          u.to_be_bytes()
+        }
+      })
+      .collect::<Vec<u8>>();
+    self.write_bytes(&bytes)
   }
 
+  /// Writes a VarInt count followed by every value as a VarLong
   #[napi]
-  pub fn clear(&mut self) {
-    self.buf.clear();
-    self.r_pos = 0;
-    self.w_pos = 0;
+  pub fn write_var_long_array(&mut self, vals: Vec<BigInt>) -> Result<(), Error> {
+    self.ensure_writable("writeVarLongArray")?;
+    // Validate everything first so a bad element doesn't leave a partial array behind
+    for val in vals.iter() {
+      bigint_to_i64(val, "writeVarLongArray")?;
+    }
+    self.write_var_int(vals.len() as i32)?;
+    for val in vals {
+      self.write_var_long(val)?;
+    }
+    Ok(())
   }
 
-  /// Returns the number of bytes this buffer can contain
+  /// Writes a VarInt count followed by every string VarInt prefixed
   #[napi]
-  pub fn get_capacity(&self) -> u32 {
-    self.buf.capacity() as u32
+  pub fn write_string_list(&mut self, list: Vec<String>) -> Result<(), Error> {
+    self.ensure_writable("writeStringList")?;
+    self.write_var_int(list.len() as i32)?;
+    for val in list {
+      self.write_string(val, None)?;
+    }
+    Ok(())
   }
 
-  /// u32 is enough, i64 is too much even for general use
+  /// Runs `callback`, rolling the writer index (and written bytes) back if it throws,
+  /// so a failed serialization doesn't leave a half-written frame behind
   #[napi]
-  pub fn set_capacity(&mut self, size: u32) {
-    if (size as usize) < self.buf.capacity() {
-      self.buf.shrink_to(size as usize);
-      unsafe { self.buf.set_len(size as usize) }
-      return;
+  pub fn write_transaction(&mut self, env: Env, callback: JsFunction) -> Result<(), Error> {
+    self.ensure_writable("writeTransaction")?;
+    let mark = self.mark_write();
+    let res = self.guard_writes("writeTransaction", || callback.call_without_args(None));
+    if let Err(e) = res {
+      self.reset_to_mark(mark);
+      return Err(rethrow(env, e));
     }
-    // TODO: might be better to use try_reserve_exact
-    self.buf.reserve_exact(size as usize - self.buf.capacity())
+    Ok(())
   }
 
-  /// Involves copying, use with caution
-  #[napi]
-  pub fn get_array(&self) -> Uint8Array {
-    Uint8Array::new(self.buf.clone())
+  fn mark_write(&self) -> WriteMark {
+    self.mark_span(usize::MAX)
   }
 
-  /// Returns the buffer, zero-copy :)
-  #[napi]
-  pub fn get_buffer(&self) -> Buffer {
-    Buffer::from(&self.buf[self.r_pos..self.w_pos])
+  /// Like `mark_write` for writes overwriting at most `span` written bytes past the writer index
+  fn mark_span(&self, span: usize) -> WriteMark {
+    let end = self.w_pos.saturating_add(span).min(self.buf.len());
+    WriteMark {
+      r_pos: self.r_pos,
+      w_pos: self.w_pos,
+      len: self.buf.len(),
+      tail: self.buf.get(self.w_pos..end).unwrap_or_default().to_vec(),
+      write_delta: self.write_delta,
+      read_delta: self.read_delta,
+      pending_slice: self.pending_slice,
+      bit_offsets: [self.read_bit, self.write_bit, self.read_bit_le, self.write_bit_le],
+    }
   }
 
-  #[napi]
-  pub fn get_readable_bytes(&self) -> u32 {
-    (self.w_pos - self.r_pos) as u32
+  /// Undoes the writes and reads made since `mark`, restoring the bytes they overwrote.
+  /// Relies on nothing having shrunk the written bytes meanwhile
+  fn reset_to_mark(&mut self, mark: WriteMark) {
+    self.buf.truncate(mark.len);
+    self.buf[mark.w_pos..mark.w_pos + mark.tail.len()].copy_from_slice(&mark.tail);
+    self.r_pos = mark.r_pos;
+    self.w_pos = mark.w_pos;
+    self.write_delta = mark.write_delta;
+    self.read_delta = mark.read_delta;
+    self.pending_slice = mark.pending_slice;
+    [self.read_bit, self.write_bit, self.read_bit_le, self.write_bit_le] = mark.bit_offsets;
   }
 
+  /// Runs `callback` to write a body, then prefixes it with its length as a VarInt,
+  /// shifting the body over when the length takes more than the 1 byte reserved for it.
+  /// Rolls everything back if the callback throws. Returns the body length
   #[napi]
-  pub fn skip_bytes(&mut self, length: u32) -> Result<(), Error> {
-    if length > self.get_readable_bytes() {
+  pub fn write_length_prefixed_struct(
+    &mut self,
+    env: Env,
+    callback: JsFunction,
+  ) -> Result<u32, Error> {
+    self.ensure_writable("writeLengthPrefixedStruct")?;
+    let mark = self.mark_write();
+    let start = mark.w_pos;
+    self.write_bytes(&[0])?;
+    let res = self.guard_writes("writeLengthPrefixedStruct", || callback.call_without_args(None));
+    let res = res.map_err(|e| rethrow(env, e));
+    let res = res.and_then(|_| self.backfill_length_prefix(start));
+    if res.is_err() {
+      self.reset_to_mark(mark);
+    }
+    res
+  }
+
+  /// Replaces the 1 byte placeholder at `mark` with the VarInt length of what follows it,
+  /// erroring if the placeholder isn't where it was left anymore
+  fn backfill_length_prefix(&mut self, mark: usize) -> Result<u32, Error> {
+    if self.w_pos <= mark || self.w_pos > self.buf.len() {
       return Err(Error::new(
-        Status::InvalidArg,
-        format!(
-          "cannot skipBytes, given length {} is greater than readableBytes {}",
-          length,
-          self.get_readable_bytes()
-        ),
+        ErrorCode::GenericFailure,
+        "cannot writeLengthPrefixedStruct, callback moved the writer index".to_string(),
+      ));
+    } else if self.buf[mark] != 0 {
+      return Err(Error::new(
+        ErrorCode::GenericFailure,
+        "cannot writeLengthPrefixedStruct, callback overwrote the length prefix".to_string(),
       ));
     }
-    self.r_pos += length as usize;
+    let length = self.w_pos - mark - 1;
+    if length > i32::MAX as usize {
+      return Err(Error::new(
+        ErrorCode::Overflow,
+        format!("cannot writeLengthPrefixedStruct, body length {} is too long", length),
+      ));
+    }
+    let (prefix, prefix_len) = encode_var_int(length as i32);
+    self.check_max_capacity(prefix_len - 1)?;
+    let old_capacity = self.buf.capacity();
+    self.buf.splice(mark..mark + 1, prefix[..prefix_len].iter().copied());
+    self.w_pos += prefix_len - 1;
+    if prefix_len > 1 {
+      self.reset_bit_offsets();
+    }
+    self.notify_grow(old_capacity)?;
+    Ok(length as u32)
+  }
+
+  /// Registers a callback fired with `(oldCapacity, newCapacity)` whenever a write
+  /// makes the backing store reallocate, pass null to remove it
+  #[napi(ts_args_type = "callback: ((oldCapacity: number, newCapacity: number) => void) | null")]
+  pub fn on_grow(&mut self, env: Env, callback: Option<GrowCallback>) -> Result<(), Error> {
+    self.ensure_idle("onGrow")?;
+    self.grow_callback = callback.map(|cb| (env, cb));
     Ok(())
   }
 
-  // READ METHODS
+  /// Registers a callback fired with `readableBytes` whenever a write or a writer index move
+  /// takes it from below `threshold` to at least `threshold`, e.g. once a whole frame has arrived.
+  /// Pass null to remove it
+  #[napi(
+    ts_args_type = "threshold: number, callback: ((readableBytes: number) => void) | null"
+  )]
+  pub fn on_readable(
+    &mut self,
+    env: Env,
+    threshold: u32,
+    callback: Option<ReadableCallback>,
+  ) -> Result<(), Error> {
+    self.ensure_idle("onReadable")?;
+    self.readable_callback = callback.map(|cb| (env, threshold, cb));
+    Ok(())
+  }
 
-  #[napi]
-  pub fn read_boolean(&mut self) -> Result<bool, Error> {
-    // TODO: Waiting for stable is_ok_and
-    let res = self.read_byte();
-    if let Ok(..) = res {
-      return Ok(res.unwrap() != 0);
+  /// Fires the `onReadable` callback if the readable bytes just crossed its threshold
+  fn notify_readable(&self, old_readable: usize) -> Result<(), Error> {
+    if let Some((env, threshold, callback)) = &self.readable_callback {
+      let threshold = *threshold as usize;
+      let readable = self.readable_bytes();
+      if old_readable < threshold && readable >= threshold {
+        self
+          .guard_callback("onReadable", || {
+            callback.borrow_back(env).and_then(|cb| cb.call(readable as u32))
+          })
+          .map_err(|e| rethrow(*env, e))?;
+      }
     }
-    Err(Error::new(
-      GenericFailure,
-      "cannot readBoolean, readableBytes is less than 1".to_string(),
-    ))
+    Ok(())
   }
 
-  #[napi]
-  pub fn read_byte(&mut self) -> Result<i32, Error> {
-    if self.get_readable_bytes() < 1 {
+  /// Writes a single value by type name, see `readScalar` for the supported names.
+  /// `long` expects a BigInt, `bool` a boolean and every other type a number
+  #[napi(ts_args_type = "typeName: string, value: number | bigint | boolean")]
+  pub fn write_scalar(&mut self, type_name: String, value: JsUnknown) -> Result<(), Error> {
+    self.ensure_writable("writeScalar")?;
+    let expected = match type_name.as_str() {
+      "byte" | "short" | "int" | "float" | "double" | "varint" => ValueType::Number,
+      "long" => ValueType::BigInt,
+      "bool" => ValueType::Boolean,
+      _ => {
+        return Err(Error::new(
+          ErrorCode::InvalidArg,
+          format!("cannot writeScalar, unknown type name {}", type_name),
+        ))
+      }
+    };
+    let actual = value.get_type().map_err(from_napi)?;
+    if actual != expected {
       return Err(Error::new(
-        GenericFailure,
-        "cannot readByte, readableBytes is less than 1".to_string(),
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot writeScalar, expected {} for {} but got {}",
+          expected, type_name, actual
+        ),
       ));
     }
-    self.r_pos += 1;
-    Ok(self.buf[self.r_pos - 1] as i8 as i32)
+    // Only reinterprets the handle, the type check above picks which view is read
+    let number = unsafe { value.cast::<JsNumber>() };
+    match type_name.as_str() {
+      "byte" => self.write_byte(number.get_int32().map_err(from_napi)?),
+      "short" => self.write_short(number.get_int32().map_err(from_napi)?),
+      "int" => self.write_int(number.get_int32().map_err(from_napi)?),
+      "long" => {
+        let (val, lossless) = unsafe { value.cast::<JsBigInt>() }
+          .get_i64()
+          .map_err(from_napi)?;
+        if !lossless {
+          return Err(Error::new(
+            ErrorCode::InvalidArg,
+            "cannot writeScalar, given BigInt does not fit in a signed long".to_string(),
+          ));
+        }
+        self.write_long(BigInt::from(val))
+      }
+      "float" => self.write_float(number.get_double().map_err(from_napi)?),
+      "double" => self.write_double(number.get_double().map_err(from_napi)?),
+      "varint" => self
+        .write_var_int(number.get_int32().map_err(from_napi)?)
+        .map(|_| ()),
+      _ => {
+        let val = unsafe { value.cast::<JsBoolean>() }.get_value();
+        self.write_boolean(val.map_err(from_napi)?)
+      }
+    }
   }
 
-  #[napi]
-  pub fn read_unsigned_byte(&mut self) -> Result<u32, Error> {
-    if self.get_readable_bytes() < 1 {
+  /// Byte-swaps `count` consecutive `width` byte values starting at the absolute `index`
+  fn swap_endianness(
+    &mut self,
+    index: u32,
+    count: u32,
+    width: usize,
+    method: &str,
+  ) -> Result<(), Error> {
+    self.ensure_writable(method)?;
+    let start = index as usize;
+    let end = start + count as usize * width;
+    if end > self.w_pos {
       return Err(Error::new(
-        GenericFailure,
-        "cannot readUnsignedByte, readableBytes is less than 1".to_string(),
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot {}, given range {}..{} exceeds writerIndex {}",
+          method, start, end, self.w_pos
+        ),
       ));
     }
-    self.r_pos += 1;
-    Ok(self.buf[self.r_pos - 1] as u32)
+    self.buf[start..end].chunks_exact_mut(width).for_each(|c| c.reverse());
+    Ok(())
   }
 
   #[napi]
-  pub fn read_short(&mut self) -> Result<i32, Error> {
-    if self.get_readable_bytes() < 2 {
-      return Err(Error::new(
-        GenericFailure,
-        "cannot readShort, readableBytes is less than 2".to_string(),
-      ));
-    }
-    self.r_pos += 2;
-    Ok(i16::from_be_bytes(
-      self.buf[self.r_pos - 2..self.r_pos]
-        .try_into()
-        .unwrap(),
-    ) as i32)
+  pub fn swap_short_endianness(&mut self, index: u32, count: u32) -> Result<(), Error> {
+    self.ensure_live("swapShortEndianness")?;
+    self.swap_endianness(index, count, 2, "swapShortEndianness")
   }
 
-  #[napi(js_name = "readShortLE")]
-  pub fn read_short_le(&mut self) -> Result<i32, Error> {
-    if self.get_readable_bytes() < 2 {
-      return Err(Error::new(
-        GenericFailure,
-        "cannot readShortLE, readableBytes is less than 2".to_string(),
-      ));
-    }
-    self.r_pos += 2;
-    Ok(i16::from_le_bytes(
-      self.buf[self.r_pos - 2..self.r_pos]
-        .try_into()
-        .unwrap(),
-    ) as i32)
+  #[napi]
+  pub fn swap_int_endianness(&mut self, index: u32, count: u32) -> Result<(), Error> {
+    self.ensure_live("swapIntEndianness")?;
+    self.swap_endianness(index, count, 4, "swapIntEndianness")
   }
 
   #[napi]
-  pub fn read_unsigned_short(&mut self) -> Result<u32, Error> {
-    if self.get_readable_bytes() < 2 {
-      return Err(Error::new(
-        GenericFailure,
-        "cannot readUnsignedShort, readableBytes is less than 2".to_string(),
-      ));
-    }
-    self.r_pos += 2;
-    Ok(u16::from_be_bytes(
-      self.buf[self.r_pos - 2..self.r_pos]
-        .try_into()
-        .unwrap(),
-    ) as u32)
+  pub fn swap_long_endianness(&mut self, index: u32, count: u32) -> Result<(), Error> {
+    self.ensure_live("swapLongEndianness")?;
+    self.swap_endianness(index, count, 8, "swapLongEndianness")
   }
 
-  #[napi(js_name = "readUnsignedShortLE")]
-  pub fn read_unsigned_short_le(&mut self) -> Result<u32, Error> {
-    if self.get_readable_bytes() < 2 {
+  /// Writes the low `count` bits of `val` into the bit offset, a zero byte is appended once
+  /// the current one is filled up. `le_variant` picks the offset and the fixed LSB-first order
+  /// of `writeBitsLE` over those of `writeBits`. `method` names the caller in errors
+  fn write_bits_ordered(
+    &mut self,
+    method: &str,
+    val: u64,
+    count: u32,
+    max_count: u32,
+    le_variant: bool,
+  ) -> Result<(), Error> {
+    self.ensure_writable(method)?;
+    Self::check_bit_count(count, max_count, method)?;
+    if count < 64 && val >> count != 0 {
       return Err(Error::new(
-        GenericFailure,
-        "cannot readUnsignedShortLE, readableBytes is less than 2".to_string(),
+        ErrorCode::InvalidArg,
+        format!("cannot {}, given value {} does not fit in {} bits", method, val, count),
       ));
     }
-    self.r_pos += 2;
-    Ok(u16::from_le_bytes(
-      self.buf[self.r_pos - 2..self.r_pos]
-        .try_into()
-        .unwrap(),
-    ) as u32)
+    let lsb_first = le_variant || self.bits_lsb_first;
+    let mut offset = if le_variant { self.write_bit_le } else { self.write_bit };
+    offset = offset.filter(|o| o.index + 1 == self.w_pos && o.index < self.buf.len());
+    for i in 0..count {
+      let o = match offset {
+        Some(o) if o.used < 8 => o,
+        _ => {
+          self.write_bytes(&[0])?;
+          BitOffset {
+            index: self.w_pos - 1,
+            used: 0,
+          }
+        }
+      };
+      if lsb_first {
+        self.buf[o.index] |= (((val >> i) & 1) << o.used) as u8;
+      } else {
+        self.buf[o.index] |= (((val >> (count - 1 - i)) & 1) << (7 - o.used)) as u8;
+      }
+      offset = Some(BitOffset {
+        index: o.index,
+        used: o.used + 1,
+      });
+    }
+    if le_variant {
+      self.write_bit_le = offset;
+    } else {
+      self.write_bit = offset;
+    }
+    Ok(())
   }
 
+  /// Writes the low `count` (1 to 32) bits of `val`, most significant bit first within each byte
+  /// unless `setBitOrder(false)` was called. Byte writes after a bit write start on a new byte
   #[napi]
-  pub fn read_medium(&mut self) -> Result<i32, Error> {
-    if self.get_readable_bytes() < 3 {
-      return Err(Error::new(
-        GenericFailure,
-        "cannot readMedium, readableBytes is less than 3".to_string(),
-      ));
-    }
-    self.r_pos += 3;
-    let res = &self.buf[(self.r_pos - 3)..self.r_pos];
-    Ok(((res[0] as i32) << 16) | ((res[1] as i32) << 8) | res[2] as i32)
-    // Ok((res[0] & 0xFF | ((res[2] & 0xFF) << 8) | ((res[3] & 0x0F) << 16)) as i32)
+  pub fn write_bits(&mut self, val: u32, count: u32) -> Result<(), Error> {
+    self.ensure_live("writeBits")?;
+    self.write_bits_ordered("writeBits", val as u64, count, 32, false)
   }
 
-  /// Appends data to the end of the buffer
-  /// tries to max out performance by using
-  /// direct memory pointers in a unsafe context
-  /// implies minimal copy.
-  pub fn write_bytes(&mut self, buf: &[u8]) {
-    let len = buf.len();
-    self.buf.reserve(len);
-    unsafe {
-      std::ptr::copy(buf.as_ptr(), self.buf.as_mut_ptr(), len);
-      self.buf.set_len(len);
-    }
-
-    // self.buf.extend_from_slice(buf);
-    self.w_pos += buf.len();
+  /// Like `writeBits` but for fields of up to 64 bits given as a BigInt.
+  /// Shares the bit offset and bit order of `writeBits`
+  #[napi]
+  pub fn write_bits_wide(&mut self, val: BigInt, count: u32) -> Result<(), Error> {
+    self.ensure_live("writeBitsWide")?;
+    let val = bigint_to_u64(&val, "writeBitsWide")?;
+    self.write_bits_ordered("writeBitsWide", val, count, 64, false)
   }
 
+  /// Picks the bit order of `readBits`/`writeBits`, most significant bit first (the default)
+  /// or least significant bit first like the `LE` variants. Starts bit accesses over on a
+  /// whole byte
   #[napi]
-  pub fn write_boolean(&mut self, val: bool) {
-    self.write_byte(val as i32);
+  pub fn set_bit_order(&mut self, msb_first: bool) {
+    self.bits_lsb_first = !msb_first;
+    self.reset_bit_offsets();
   }
 
-  /// Writes both a signed / unsigned byte
-  #[napi]
-  pub fn write_byte(&mut self, val: i32) {
-    self.buf.push(val as u8);
-    self.w_pos += 1;
+  /// Writes the low `count` (1 to 32) bits of `val` least significant bit first, like deflate.
+  /// Keeps its own bit offset, separate from `writeBits` even after `setBitOrder(false)`,
+  /// so switching between the two mid-byte moves on to a new byte
+  #[napi(js_name = "writeBitsLE")]
+  pub fn write_bits_le(&mut self, val: u32, count: u32) -> Result<(), Error> {
+    self.ensure_live("writeBitsLE")?;
+    self.write_bits_ordered("writeBitsLE", val as u64, count, 32, true)
   }
 
+  /// Writes zero bytes until the writer index is a multiple of `boundary`
   #[napi]
-  pub fn write_short(&mut self, val: i32) {
-    self.write_bytes(&(val as i16).to_be_bytes());
+  pub fn align_writer(&mut self, boundary: u32) -> Result<(), Error> {
+    self.ensure_writable("alignWriter")?;
+    let padding = Self::padding_to(self.w_pos, boundary, "alignWriter")?;
+    self.write_bytes(&vec![0; padding])
+  }
+
+  /// Appends `count` bytes, each one returned by `producer` called with its index.
+  /// Nothing is written if `producer` throws, and it can't call back into this buffer
+  #[napi(ts_args_type = "count: number, producer: (index: number) => number")]
+  pub fn fill(&mut self, env: Env, count: u32, producer: JsFunction) -> Result<(), Error> {
+    self.ensure_writable("fill")?;
+    let mut bytes = Vec::with_capacity(count as usize);
+    for i in 0..count {
+      let byte = self
+        .guard_callback("fill", || {
+          env
+            .create_uint32(i)
+            .and_then(|index| producer.call(None, &[index]))
+            .and_then(|val| val.coerce_to_number())
+            .and_then(|val| val.get_int32())
+        })
+        .map_err(|e| rethrow(env, e))?;
+      bytes.push(byte as u8);
+    }
+    self.write_bytes(&bytes)
   }
 
-  // TODO
+  /// Overwrites the big-endian int at the absolute `index` without moving either index,
+  /// the int must lie within the written bytes
   #[napi]
-  pub fn write_medium(&mut self, val: i32) {
-    // TODO
-    // bytes.push((num >> 16) as u8);
-    // bytes.push((num >> 8) as u8);
-    // bytes.push(num as u8);
-    self.buf.resize(3, 0);
-    self.buf[self.w_pos] = (val >> 16) as u8;
-    self.buf[self.w_pos + 1] = (val >> 8) as u8;
-    self.buf[self.w_pos + 2] = val as u8;
-    self.w_pos += 3;
+  pub fn set_int(&mut self, index: u32, val: i32) -> Result<(), Error> {
+    self.ensure_writable("setInt")?;
+    let start = self.check_range(index, 4, "setInt")?;
+    self.buf[start..start + 4].copy_from_slice(&val.to_be_bytes());
+    Ok(())
+  }
+
+  /// Writes each value with the type name at the same position in `spec` (see `writeScalar`)
+  /// in a single call, rolling the written bytes back if any of them fails
+  #[napi(ts_args_type = "spec: Array<string>, values: Array<number | bigint | boolean>")]
+  pub fn write_batch(&mut self, spec: Vec<String>, values: Vec<JsUnknown>) -> Result<(), Error> {
+    self.ensure_writable("writeBatch")?;
+    if spec.len() != values.len() {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot writeBatch, given {} type names but {} values",
+          spec.len(),
+          values.len()
+        ),
+      ));
+    }
+    let mark = self.mark_write();
+    for (type_name, value) in spec.into_iter().zip(values) {
+      if let Err(e) = self.write_scalar(type_name, value) {
+        self.reset_to_mark(mark);
+        return Err(e);
+      }
+    }
+    Ok(())
+  }
+
+  /// Writes `value`'s fields in the order and with the types given by `fields` (see `readStruct`),
+  /// rolling the written bytes back on a missing key or a type mismatch
+  #[napi(
+    ts_args_type = "fields: Array<StructField>, value: Record<string, number | bigint | boolean | string>"
+  )]
+  pub fn write_struct(&mut self, fields: Vec<StructField>, value: JsObject) -> Result<(), Error> {
+    self.ensure_writable("writeStruct")?;
+    let mark = self.mark_write();
+    let res = self.write_struct_fields(fields, value);
+    if res.is_err() {
+      self.reset_to_mark(mark);
+    }
+    res
+  }
+
+  fn write_struct_fields(
+    &mut self,
+    fields: Vec<StructField>,
+    value: JsObject,
+  ) -> Result<(), Error> {
+    for field in fields {
+      if !value.has_named_property(&field.name).map_err(from_napi)? {
+        return Err(Error::new(
+          ErrorCode::InvalidArg,
+          format!("cannot writeStruct, given value is missing field {}", field.name),
+        ));
+      }
+      let val: JsUnknown = value.get_named_property(&field.name).map_err(from_napi)?;
+      if field.type_name != "string" {
+        self.write_scalar(field.type_name, val)?;
+        continue;
+      }
+      let actual = val.get_type().map_err(from_napi)?;
+      if actual != ValueType::String {
+        return Err(Error::new(
+          ErrorCode::InvalidArg,
+          format!(
+            "cannot writeStruct, expected String for {} but got {}",
+            field.name, actual
+          ),
+        ));
+      }
+      let utf8 = unsafe { val.cast::<JsString>() }.into_utf8().map_err(from_napi)?;
+      self.write_string(utf8.into_owned().map_err(from_napi)?, None)?;
+    }
+    Ok(())
   }
 
   #[napi]
   pub fn set_reader_index(&mut self, index: u32) -> Result<(), Error> {
+    self.ensure_live("setReaderIndex")?;
     // This comparison is useless as we're using unsigned integers, but still, i'll keep it here
     // if index < 0 {
-    //  return Err(Error::new(Status::InvalidArg, format!("cannot set reader index, given readerIndex {} is less than 0", index)))
+    //  return Err(Error::new(ErrorCode::InvalidArg, format!("cannot set reader index, given readerIndex {} is less than 0", index)))
     if (index as usize) > self.w_pos {
       return Err(Error::new(
-        Status::InvalidArg,
+        ErrorCode::InvalidArg,
         format!(
           "cannot set readerIndex, given readerIndex {} is greater than writerIndex {}",
           index, self.w_pos
@@ -281,12 +3328,52 @@ impl ByteBuf {
       ));
     }
     self.r_pos = index as usize;
+    self.reset_read_bit_offsets();
+    self.read_delta = 0;
+    Ok(())
+  }
+
+  /// Returns a cursor starting at the reader index, reading it never moves this buffer's
+  /// reader index. It reads a snapshot: bytes written afterwards aren't visible to it
+  #[napi]
+  pub fn cursor(&self) -> Result<ByteBufCursor, Error> {
+    self.ensure_live("cursor")?;
+    let mut inner = ByteBuf::from_vec(self.buf[..self.w_pos].to_vec());
+    inner.r_pos = self.r_pos;
+    inner.default_order = self.default_order;
+    inner.read_only = true;
+    Ok(ByteBufCursor { inner })
+  }
+
+  /// Like `setReaderIndex` but also rejects indices past the written bytes
+  #[napi]
+  pub fn set_reader_index_strict(&mut self, index: u32) -> Result<(), Error> {
+    self.ensure_live("setReaderIndexStrict")?;
+    self.check_written("setReaderIndexStrict", "readerIndex", index)?;
+    self.set_reader_index(index)
+  }
+
+  /// Errors if `index` points past the bytes actually written, into reserved capacity
+  fn check_written(&self, method: &str, name: &str, index: u32) -> Result<(), Error> {
+    if index as usize > self.buf.len() {
+      return Err(Error::new(
+        ErrorCode::InvalidArg,
+        format!(
+          "cannot {}, given {} {} is greater than written length {}",
+          method,
+          name,
+          index,
+          self.buf.len()
+        ),
+      ));
+    }
     Ok(())
   }
 
   #[napi]
-  pub fn get_reader_index(&self) -> u32 {
-    self.r_pos as u32
+  pub fn get_reader_index(&self) -> Result<u32, Error> {
+    self.ensure_live("getReaderIndex")?;
+    to_u32(self.r_pos, "readerIndex")
   }
 
   /* #[napi]
@@ -299,19 +3386,49 @@ impl ByteBuf {
     self.r_pos = 0;
   } */
 
+  /// Moves the readable bytes to the front of the buffer, like Netty's compact.
+  /// Unlike a shrinking discard the capacity is left untouched, so the freed
+  /// tail is reused by the following writes
+  #[napi]
+  pub fn compact(&mut self) -> Result<(), Error> {
+    self.ensure_writable("compact")?;
+    self.ensure_stable("compact")?;
+    self.buf.copy_within(self.r_pos..self.w_pos, 0);
+    self.w_pos -= self.r_pos;
+    self.r_pos = 0;
+    self.buf.truncate(self.w_pos);
+    self.reset_bit_offsets();
+    self.pending_slice = None;
+    Ok(())
+  }
+
+  /// Netty's heuristic discard: only compacts once the reader index reaches half
+  /// the capacity (or everything was read), amortizing the move over many small reads
+  #[napi]
+  pub fn discard_some_read_bytes(&mut self) -> Result<(), Error> {
+    self.ensure_writable("discardSomeReadBytes")?;
+    self.ensure_stable("discardSomeReadBytes")?;
+    if self.r_pos > 0 && (self.r_pos == self.w_pos || self.r_pos >= self.buf.capacity() / 2) {
+      self.compact()?;
+    }
+    Ok(())
+  }
+
   #[napi]
   pub fn set_writer_index(&mut self, index: u32) -> Result<(), Error> {
+    self.ensure_live("setWriterIndex")?;
+    self.ensure_stable("setWriterIndex")?;
     if (index as usize) < self.r_pos {
       return Err(Error::new(
-        Status::InvalidArg,
+        ErrorCode::InvalidArg,
         format!(
           "cannot setWriterIndex, given writerIndex {} is less than readerIndex {}",
           index, self.r_pos
         ),
       ));
-    } else if (index as usize) > self.buf.capacity() {
+    } else if (index as usize) > self.buf.capacity() && !self.auto_grow {
       return Err(Error::new(
-        Status::InvalidArg,
+        ErrorCode::InvalidArg,
         format!(
           "cannot setWriterIndex, given writerIndex {} is greater than capacity {}",
           index,
@@ -319,30 +3436,71 @@ impl ByteBuf {
         ),
       ));
     }
+    let old_capacity = self.buf.capacity();
+    let old_readable = self.readable_bytes();
+    let mark = self.watched().then(|| self.mark_span(0));
+    self.zero_fill_to("setWriterIndex", index as usize)?;
     self.w_pos = index as usize;
+    self.reset_write_bit_offsets();
+    self.write_delta = 0;
+    self.notify_write(mark, old_capacity, old_readable)
+  }
+
+  /// Zeroes the never written bytes up to `index`, so the indices never point past the
+  /// initialized bytes. Grows the buffer first when auto grow is on
+  fn zero_fill_to(&mut self, method: &str, index: usize) -> Result<(), Error> {
+    if index <= self.buf.len() {
+      return Ok(());
+    }
+    if self.auto_grow {
+      self.ensure_writable(method)?;
+      self.check_max_capacity(index.saturating_sub(self.w_pos))?;
+      self.reserve(index - self.buf.len());
+    }
+    self.buf.resize(index, 0);
     Ok(())
   }
 
+  /// When enabled, `setWriterIndex` and `setIndex` past the capacity grow the buffer and
+  /// zero-fill the gap instead of erroring. Off by default
+  #[napi]
+  pub fn set_auto_grow(&mut self, enabled: bool) {
+    self.auto_grow = enabled;
+  }
+
+  /// Like `setWriterIndex` but only within the written bytes, erroring instead of
+  /// zero-filling reserved capacity
   #[napi]
-  pub fn get_writer_index(&self) -> u32 {
-    self.w_pos as u32
+  pub fn set_writer_index_strict(&mut self, index: u32) -> Result<(), Error> {
+    self.ensure_live("setWriterIndexStrict")?;
+    self.ensure_stable("setWriterIndexStrict")?;
+    self.check_written("setWriterIndexStrict", "writerIndex", index)?;
+    self.set_writer_index(index)
+  }
+
+  #[napi]
+  pub fn get_writer_index(&self) -> Result<u32, Error> {
+    self.ensure_live("getWriterIndex")?;
+    to_u32(self.w_pos, "writerIndex")
   }
 
   #[napi]
   pub fn set_index(&mut self, r_index: u32, w_index: u32) -> Result<(), Error> {
+    self.ensure_live("setIndex")?;
+    self.ensure_stable("setIndex")?;
     // if r_index < 0 {
-    //  return Err(Error::new(Status::InvalidArg, format!("cannot setIndex, given readerIndex {} is less than 0", r_index)))
+    //  return Err(Error::new(ErrorCode::InvalidArg, format!("cannot setIndex, given readerIndex {} is less than 0", r_index)))
     if w_index < r_index {
       return Err(Error::new(
-        Status::InvalidArg,
+        ErrorCode::InvalidArg,
         format!(
           "cannot setIndex, given writerIndex {} is less than given readerIndex {}",
           r_index, w_index
         ),
       ));
-    } else if w_index as usize > self.buf.capacity() {
+    } else if w_index as usize > self.buf.capacity() && !self.auto_grow {
       return Err(Error::new(
-        Status::InvalidArg,
+        ErrorCode::InvalidArg,
         format!(
           "cannot setIndex, given writerIndex {} is greater than capacity {}",
           w_index,
@@ -351,8 +3509,171 @@ impl ByteBuf {
       ));
     }
 
+    let old_capacity = self.buf.capacity();
+    let old_readable = self.readable_bytes();
+    let mark = self.watched().then(|| self.mark_span(0));
+    self.zero_fill_to("setIndex", w_index as usize)?;
     self.w_pos = w_index as usize;
     self.r_pos = r_index as usize;
-    Ok(())
+    self.reset_bit_offsets();
+    self.write_delta = 0;
+    self.read_delta = 0;
+    self.notify_write(mark, old_capacity, old_readable)
+  }
+
+  /// Clamps the indices back into `0 <= readerIndex <= writerIndex <= written length` and returns
+  /// whether anything changed. The index setters already keep them there, this is a safety net
+  #[napi]
+  pub fn normalize_indices(&mut self) -> Result<bool, Error> {
+    self.ensure_live("normalizeIndices")?;
+    self.ensure_stable("normalizeIndices")?;
+    let w_pos = self.w_pos.min(self.buf.len());
+    let r_pos = self.r_pos.min(w_pos);
+    if (r_pos, w_pos) == (self.r_pos, self.w_pos) {
+      return Ok(false);
+    }
+    self.r_pos = r_pos;
+    self.w_pos = w_pos;
+    self.reset_bit_offsets();
+    self.write_delta = 0;
+    self.read_delta = 0;
+    Ok(true)
+  }
+}
+
+/// Independent reader over a snapshot of a buffer's written bytes, see `ByteBuf.cursor`
+#[napi]
+pub struct ByteBufCursor {
+  inner: ByteBuf,
+}
+
+#[napi]
+impl ByteBufCursor {
+  /// Absolute index of the next byte to read
+  #[napi]
+  pub fn get_position(&self) -> Result<u32, Error> {
+    self.inner.get_reader_index()
+  }
+
+  #[napi]
+  pub fn set_position(&mut self, index: u32) -> Result<(), Error> {
+    self.inner.set_reader_index(index)
+  }
+
+  #[napi]
+  pub fn get_readable_bytes(&self) -> Result<u32, Error> {
+    self.inner.get_readable_bytes()
+  }
+
+  #[napi]
+  pub fn skip_bytes(&mut self, length: u32) -> Result<(), Error> {
+    self.inner.skip_bytes(length)
+  }
+
+  #[napi]
+  pub fn read_boolean(&mut self) -> Result<bool, Error> {
+    self.inner.read_boolean()
+  }
+
+  #[napi]
+  pub fn read_byte(&mut self) -> Result<i32, Error> {
+    self.inner.read_byte()
+  }
+
+  #[napi]
+  pub fn read_unsigned_byte(&mut self) -> Result<u32, Error> {
+    self.inner.read_unsigned_byte()
+  }
+
+  #[napi]
+  pub fn read_short(&mut self) -> Result<i32, Error> {
+    self.inner.read_short()
+  }
+
+  #[napi]
+  pub fn read_unsigned_short(&mut self) -> Result<u32, Error> {
+    self.inner.read_unsigned_short()
+  }
+
+  #[napi]
+  pub fn read_medium(&mut self) -> Result<i32, Error> {
+    self.inner.read_medium()
+  }
+
+  #[napi]
+  pub fn read_int(&mut self) -> Result<i32, Error> {
+    self.inner.read_int()
+  }
+
+  #[napi]
+  pub fn read_long(&mut self) -> Result<BigInt, Error> {
+    self.inner.read_long()
+  }
+
+  #[napi]
+  pub fn read_float(&mut self) -> Result<f64, Error> {
+    self.inner.read_float()
+  }
+
+  #[napi]
+  pub fn read_double(&mut self) -> Result<f64, Error> {
+    self.inner.read_double()
+  }
+
+  #[napi]
+  pub fn read_var_int(&mut self, max_bits: Option<u32>) -> Result<i32, Error> {
+    self.inner.read_var_int(max_bits)
+  }
+
+  #[napi]
+  pub fn read_string(&mut self, encoding: Option<StringEncoding>) -> Result<String, Error> {
+    self.inner.read_string(encoding)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_i32_rejects_values_past_i32_max() {
+    assert_eq!(to_i32(i32::MAX as usize, "index").unwrap(), i32::MAX);
+    let err = to_i32(i32::MAX as usize + 1, "index").unwrap_err();
+    assert_eq!(err.status.as_ref(), "OVERFLOW");
+    assert_eq!(err.reason, "cannot get index, 2147483648 is greater than 2147483647");
+  }
+
+  #[test]
+  fn to_u32_rejects_values_past_u32_max() {
+    assert_eq!(to_u32(u32::MAX as usize, "index").unwrap(), u32::MAX);
+    let err = to_u32(u32::MAX as usize + 1, "index").unwrap_err();
+    assert_eq!(err.status.as_ref(), "OVERFLOW");
+    assert_eq!(err.reason, "cannot get index, 4294967296 is greater than 4294967295");
+  }
+
+  #[test]
+  fn normalize_indices_clamps_past_written_length() {
+    // Dropping would pull in the napi symbols only the Node process provides
+    let mut buf = std::mem::ManuallyDrop::new(ByteBuf::from_vec(vec![0, 0, 0, 1]));
+    buf.r_pos = 10;
+    buf.w_pos = 12;
+    assert!(buf.normalize_indices().unwrap());
+    assert_eq!((buf.r_pos, buf.w_pos), (4, 4));
+    assert!(!buf.normalize_indices().unwrap());
+  }
+
+  fn spare_after_clear(zero_on_clear: bool) -> Vec<u8> {
+    let mut buf = std::mem::ManuallyDrop::new(ByteBuf::from_vec(Vec::with_capacity(16)));
+    buf.zero_on_clear = zero_on_clear;
+    buf.buf.extend_from_slice(&[0xff; 8]);
+    buf.w_pos = 8;
+    buf.reset();
+    buf.buf.spare_capacity_mut()[..8].iter().map(|b| unsafe { b.assume_init() }).collect()
+  }
+
+  #[test]
+  fn clear_zeroes_spare_capacity() {
+    assert_eq!(spare_after_clear(true), [0; 8]);
+    assert_eq!(spare_after_clear(false), [0xff; 8]);
   }
 }